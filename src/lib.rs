@@ -0,0 +1,69 @@
+//! Decoder for MRT routing information export files (RFC 6396), as published
+//! by collectors like RIPE RIS or RouteViews: TABLE_DUMP, TABLE_DUMP_V2 and
+//! BGP4MP records along with their BGP path attributes.
+//!
+//! [`mrt::MRTMessageReader`] splits a stream into records, the types of
+//! [`mrt::message`] decode their payload:
+//!
+//! ```no_run
+//! use mrtdump::mrt::message::{Afi, PeerIndexTable, RibUnicast, Safi};
+//! use mrtdump::mrt::{MRTMessageReader, MRTSubType};
+//! use std::fs::File;
+//!
+//! # fn main() -> mrtdump::mrt::Result<()> {
+//! let mut reader = MRTMessageReader::new(File::open("rib.20250701.0000")?);
+//! let mut peer_index_table = None;
+//! while let Some(mut message) = reader.read_message()? {
+//!     match message.header.mrt_subtype {
+//!         MRTSubType::PeerIndexTable => {
+//!             peer_index_table = Some(PeerIndexTable::from_reader(&mut message.payload)?);
+//!         }
+//!         MRTSubType::RibIpV4Unicast => {
+//!             let Some(peer_index_table) = &peer_index_table else {
+//!                 continue;
+//!             };
+//!             let rib = RibUnicast::from_reader(
+//!                 &mut message.payload,
+//!                 peer_index_table,
+//!                 message.header.ts,
+//!                 Afi::Ipv4,
+//!                 Safi::Unicast,
+//!                 false,
+//!             )?;
+//!             for entry in rib.rib_entries() {
+//!                 let as_path = entry.attributes.as_path_string();
+//!                 println!("{} {} {}", rib.prefix(), entry.peer_ip, as_path);
+//!             }
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`mrt::MRTSliceReader`] reads the records of a byte slice or of a file
+//! mapped with [`mrt::map_file`] without copying them. The entries and
+//! attributes of the RIB_IPV4_* and RIB_IPV6_* records are decoded on access,
+//! the other records are left to the parsers of [`mrt::message`].
+//!
+//! [`mrt::MRTReader`] does the same as the example above and yields the
+//! decoded records, keeping the peer index table for the RIB records:
+//!
+//! ```no_run
+//! use mrtdump::mrt::{MRTReader, MRTRecord};
+//! use std::fs::File;
+//!
+//! # fn main() -> mrtdump::mrt::Result<()> {
+//! for record in MRTReader::new(File::open("rib.20250701.0000")?) {
+//!     match record {
+//!         Ok(MRTRecord::RibUnicast(rib)) => println!("{}", rib.prefix()),
+//!         Ok(_) => {}
+//!         Err(e) => eprintln!("{}", e),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod mrt;
@@ -1,15 +1,13 @@
-mod mrt;
-
-use chrono::{DateTime, Utc};
-use clap::Parser;
-use mrt::{
-    Error, MRTMessage, MRTSubType, MRTType, Result, message::PeerIndexTable,
-    message::RibIpV4Unicast,
+use clap::{Parser, ValueEnum};
+use mrtdump::mrt::{
+    Error, MRTHeader, MRTReader, MRTRecord, MRTSubType, MRTType, Result, message::MalformedRoutes,
+    message::WriteRecords,
 };
 
 use std::{
+    collections::BTreeMap,
     fs::File,
-    io::{BufReader, BufWriter, Cursor, Read, prelude::*},
+    io::{BufReader, BufWriter, Read, prelude::*},
     path::Path,
     process::exit,
 };
@@ -34,6 +32,62 @@ struct Args {
     #[arg(short, long)]
     /// Optional Output file path
     output_file: Option<String>,
+    /// Show the flags of the path attributes in multi-line output
+    #[arg(short, long, default_value_t = false)]
+    verbose: bool,
+    /// What to do with routes carrying malformed attributes (RFC 7606)
+    #[arg(long, value_enum, default_value_t = Malformed::Emit)]
+    malformed: Malformed,
+    /// Skip the records of unsupported types instead of stopping
+    #[arg(short, long, default_value_t = false)]
+    lenient: bool,
+    /// Add the peer locations of the GEO_PEER_TABLE to the RIB entries
+    #[arg(short, long, default_value_t = false)]
+    geo: bool,
+    /// Resume on the next valid record after a corrupted one
+    #[arg(short, long, default_value_t = false)]
+    recover: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Malformed {
+    /// Print the routes as they were decoded
+    Emit,
+    /// Print the routes with the most severe error action
+    Mark,
+    /// Drop the routes and attributes a router would discard or withdraw
+    Drop,
+}
+
+/// Number of records skipped in lenient mode, or written without their
+/// routes, by kind
+#[derive(Debug, Default)]
+struct SkippedRecords(BTreeMap<String, u64>);
+
+impl SkippedRecords {
+    fn add(&mut self, kind: String) {
+        *self.0.entry(kind).or_default() += 1;
+    }
+
+    fn print_summary(&self) {
+        if self.0.is_empty() {
+            return;
+        }
+        eprintln!("Skipped records:");
+        for (kind, count) in &self.0 {
+            eprintln!("  {}: {}", kind, count);
+        }
+    }
+}
+
+/// Skip a record of a known type that doesn't belong to the current dump in
+/// lenient mode, it is an error otherwise
+fn skip_record(header: &MRTHeader, arg: &Args, skipped: &mut SkippedRecords) -> Result<()> {
+    if !arg.lenient {
+        return Err(Error::InvalidMrtType(header.mrt_type, header.mrt_subtype));
+    }
+    skipped.add(format!("{}/{}", header.mrt_type, header.mrt_subtype));
+    Ok(())
 }
 
 fn open_file_without_extension(filename: &str, capacity: usize) -> Result<Box<dyn Read>> {
@@ -77,88 +131,166 @@ fn gen_writer(file: &Option<String>) -> Result<Box<dyn std::io::Write>> {
     }
 }
 
-fn read_rib_ipv4_unicast<R: Read, W: Write>(
-    reader: &mut R,
+/// Apply the --malformed option to a record, returns false when it must be
+/// dropped
+fn apply_malformed(record: &mut impl MalformedRoutes, arg: &Args) -> bool {
+    match arg.malformed {
+        Malformed::Emit => true,
+        Malformed::Mark => {
+            record.mark_malformed();
+            true
+        }
+        Malformed::Drop => record.drop_malformed(),
+    }
+}
+
+/// Write a record in the format of the options, after the --malformed option
+fn write_records<T: WriteRecords + MalformedRoutes, W: Write>(
+    mut record: T,
     writer: &mut W,
-    peer_index_table: &PeerIndexTable,
-    timestamp: DateTime<Utc>,
     arg: &Args,
 ) -> Result<()> {
-    let rib_ipv4_unicast = RibIpV4Unicast::from_reader(reader, peer_index_table, timestamp)?;
+    if !apply_malformed(&mut record, arg) {
+        return Ok(());
+    }
     if arg.json {
-        rib_ipv4_unicast.write_json_records(writer)?;
+        record.write_json_records(writer)?;
     } else if arg.csv {
-        rib_ipv4_unicast.write_csv_records(writer)?;
+        record.write_csv_records(writer)?;
     } else {
-        rib_ipv4_unicast.write_multiline_records(writer)?;
+        record.write_multiline_records(writer, arg.verbose)?;
     }
     Ok(())
 }
 
-fn read_table_dump_v2<R: Read, W: Write>(
-    reader: &mut R,
+/// Write a decoded record, the tables only feed the RIB records that follow
+fn write_record<W: Write>(record: MRTRecord, writer: &mut W, arg: &Args) -> Result<()> {
+    match record {
+        MRTRecord::RibUnicast(rib_unicast) => write_records(rib_unicast, writer, arg),
+        MRTRecord::RibGeneric(rib_generic) => write_records(rib_generic, writer, arg),
+        MRTRecord::TableDump(table_dump) => write_records(table_dump, writer, arg),
+        MRTRecord::Bgp4MpMessage(bgp4mp_message) => write_records(bgp4mp_message, writer, arg),
+        MRTRecord::Bgp4MpStateChange(bgp4mp_state_change) => {
+            write_records(bgp4mp_state_change, writer, arg)
+        }
+        MRTRecord::PeerIndexTable(_) | MRTRecord::GeoPeerTable(_) | MRTRecord::Unknown { .. } => {
+            Ok(())
+        }
+    }
+}
+
+/// Type of the records of a dump, BGP4MP_ET records mix with BGP4MP ones
+fn dump_type(mrt_type: MRTType) -> MRTType {
+    match mrt_type {
+        MRTType::Bgp4MpEt => MRTType::Bgp4Mp,
+        mrt_type => mrt_type,
+    }
+}
+
+/// Read the records of the dump: its type is set by the first record, a
+/// TABLE_DUMP_V2 dump starting with its PEER_INDEX_TABLE. In lenient mode the
+/// records of unknown type or subtype and those of another dump are counted
+/// and skipped, in recovery mode the reading resumes on the next valid record
+/// after a corrupted one
+fn read_dump<R: Read, W: Write>(
+    reader: &mut MRTReader<R>,
     writer: &mut W,
-    peer_index_table: &mut Cursor<Vec<u8>>,
     arg: &Args,
-) -> Result<()> {
-    // Read the table dump v2
-    let peer_index_table = PeerIndexTable::from_reader(peer_index_table)?;
-    while let Ok(mut message) = MRTMessage::from_reader(reader) {
-        // Match the message type and subtype
-        match (message.header.mrt_type, message.header.mrt_subtype) {
-            (MRTType::TableDumpV2, MRTSubType::RibIpV4Unicast) => read_rib_ipv4_unicast(
-                &mut message.payload,
-                writer,
-                &peer_index_table,
-                message.header.ts,
-                arg,
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error reading RIB IPv4 Unicast: {} skip the entry", e);
-            }),
-            _ => {
-                return Err(Error::InvalidMrtType(
-                    message.header.mrt_type,
-                    message.header.mrt_subtype,
+    skipped: &mut SkippedRecords,
+) -> std::result::Result<(), String> {
+    let mut dump = None;
+    let mut corrupted = None; // Corrupted record and its offset, reported on resync
+    loop {
+        let offset = reader.offset();
+        let next = reader.next();
+        if let Some((e, corrupted_offset)) = corrupted.take() {
+            match next {
+                Some(_) => eprintln!(
+                    "{}, skipped {} bytes to resume at offset {}",
+                    e,
+                    offset - corrupted_offset,
+                    offset
+                ),
+                None => eprintln!("{}, no valid record until the end of the file", e),
+            }
+        }
+        let record = match next {
+            None => return Ok(()),
+            Some(Ok(record)) => record,
+            Some(Err(e @ Error::InvalidRecord(..))) => {
+                eprintln!("{}, skip the entry", e);
+                continue;
+            }
+            Some(Err(e @ Error::CorruptedRecord(..))) if arg.recover => {
+                corrupted = Some((e, offset));
+                continue;
+            }
+            Some(Err(e)) => return Err(format!("Error reading MRT records, {}", e)),
+        };
+        if let MRTRecord::Unknown {
+            mrt_type,
+            mrt_subtype,
+        } = record
+        {
+            if !arg.lenient {
+                return Err(Error::UnsupportedMrtRecord(mrt_type, mrt_subtype).to_string());
+            }
+            skipped.add(format!("type {} subtype {}", mrt_type, mrt_subtype));
+            continue;
+        }
+        let Some(&header) = reader.header() else {
+            return Err("Error reading MRT records, record without a header".to_string());
+        };
+        let mrt_type = dump_type(header.mrt_type);
+        if dump.is_none()
+            && (mrt_type != MRTType::TableDumpV2
+                || header.mrt_subtype == MRTSubType::PeerIndexTable)
+        {
+            dump = Some(mrt_type);
+        }
+        if dump != Some(mrt_type) {
+            if dump.is_none() && !arg.lenient {
+                return Err(format!(
+                    "Unable to read entry of type: {:?} with subtype {:?}",
+                    header.mrt_type, header.mrt_subtype
                 ));
             }
+            skip_record(&header, arg, skipped).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let MRTRecord::RibGeneric(rib_generic) = &record
+            && !rib_generic.has_entries_decoded()
+        {
+            skipped.add(format!(
+                "{} entries, NLRI not decoded",
+                rib_generic.afi_safi_name()
+            ));
         }
+        write_record(record, writer, arg).map_err(|e| format!("Error writing records, {}", e))?;
     }
-    writer.flush()?;
-    Ok(())
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
     // open the file
-    let mut file = open_file(&args.input_file).unwrap_or_else(|_| {
+    let file = open_file(&args.input_file).unwrap_or_else(|_| {
         eprintln!("Failed to open file: {}", args.input_file);
         exit(1);
     });
+    let mut reader = MRTReader::new(file)
+        .recover(args.recover)
+        .locate_peers(args.geo);
 
     let mut writer = gen_writer(&args.output_file)?;
 
-    // Read the first message
-    let mut message = MRTMessage::from_reader(&mut file)?;
-
-    // Match the first message type and subtype and parse it
-    match (message.header.mrt_type, message.header.mrt_subtype) {
-        (MRTType::TableDumpV2, MRTSubType::PeerIndexTable) => {
-            // Read the peer index table and print the subsequent messages associated to it
-            read_table_dump_v2(&mut file, &mut writer, &mut message.payload, &args).unwrap_or_else(
-                |e| {
-                    eprintln!("Error reading table dump v2, {}", e);
-                    exit(1);
-                },
-            )
-        }
-        (t1, t2) => {
-            eprintln!(
-                "Unable to read entry of type: {:?} with subtype {:?}",
-                t1, t2
-            );
-            exit(1);
-        }
+    let mut skipped = SkippedRecords::default();
+    let result = read_dump(&mut reader, &mut writer, &args, &mut skipped);
+    // Whatever was decoded before an error is still written out
+    writer.flush()?;
+    skipped.print_summary();
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        exit(1);
     }
     Ok(())
 }
@@ -11,10 +11,18 @@ pub enum Error {
     BadMrtSubtype(u16),
     #[error("Unable to parse MRT header")]
     BadMrtHeader,
-    #[error("Error parsing BGP attribute number: {0}")]
-    ParsingBgpAttribute(u8),
     #[error("Wrong MRT type or subtype")]
     InvalidMrtType(MRTType, MRTSubType),
+    #[error("Unsupported MRT record of type {0} subtype {1}")]
+    UnsupportedMrtRecord(u16, u16),
+    #[error("Truncated MRT record {1} at offset {0}")]
+    TruncatedRecord(u64, u64),
+    #[error("Corrupted MRT record {1} at offset {0}")]
+    CorruptedRecord(u64, u64),
+    #[error("Invalid MRT record {1} at offset {0}: {2}")]
+    InvalidRecord(u64, u64, Box<Error>),
+    #[error("RIB record without a peer index table")]
+    MissingPeerIndexTable,
     #[error("I/O error: {0}")]
     IO(#[from] std::io::Error),
     #[error("UTF-8 error: {0}")]
@@ -27,8 +35,34 @@ pub enum Error {
     InvalidCommunityLength(u16),
     #[error("Invalid large community length: {0}")]
     InvalidLargeCommunityLength(u16),
+    #[error("Invalid extended community length: {0}")]
+    InvalidExtendedCommunityLength(u16),
     #[error("CSV error: {0}")]
     CsvSerialization(#[from] csv::Error),
     #[error("Bad RIB entry header")]
     BadRibEntryHeader,
+    #[error("Bad prefix length: {0}")]
+    BadPrefixLength(u8),
+    #[error("Bad or unsupported address family: {0}")]
+    BadAfi(u16),
+    #[error("Bad or unsupported BGP message type: {0}")]
+    BadBgpMessageType(u8),
+    #[error("Bad BGP message length: {0}")]
+    BadBgpMessageLength(u16),
+    #[error("Bad BGP FSM state: {0}")]
+    BadBgpState(u16),
+    #[error("Invalid next hop length: {0}")]
+    InvalidNextHopLength(u8),
+    #[error("Bad AS_PATH segment type: {0}")]
+    BadAsPathSegmentType(u8),
+    #[error("Invalid aggregator length: {0}")]
+    InvalidAggregatorLength(u16),
+    #[error("Invalid cluster list length: {0}")]
+    InvalidClusterListLength(u16),
+    #[error("Bad length {1} for attribute {0}")]
+    BadAttributeLength(u8, u16),
+    #[error("Bad value {1} for attribute {0}")]
+    BadAttributeValue(u8, u8),
+    #[error("Missing well-known attribute {0}")]
+    MissingAttribute(u8),
 }
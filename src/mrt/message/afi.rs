@@ -0,0 +1,44 @@
+use serde::Serialize;
+use strum_macros::{Display, FromRepr};
+
+/// Address Family Identifier (IANA "Address Family Numbers")
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u16)]
+pub enum Afi {
+    #[strum(serialize = "IPV4")]
+    Ipv4 = 1,
+    #[strum(serialize = "IPV6")]
+    Ipv6 = 2,
+}
+
+/// Subsequent Address Family Identifier (IANA "SAFI Values")
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u8)]
+pub enum Safi {
+    #[strum(serialize = "UNICAST")]
+    Unicast = 1,
+    #[strum(serialize = "MULTICAST")]
+    Multicast = 2,
+    #[strum(serialize = "MPLS_LABEL")]
+    MplsLabel = 4,
+    #[strum(serialize = "MPLS_VPN")]
+    MplsVpn = 128,
+    #[strum(serialize = "MULTICAST_MPLS_VPN")]
+    MulticastMplsVpn = 129,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_afi_safi_from_repr() {
+        assert_eq!(Afi::from_repr(2), Some(Afi::Ipv6));
+        assert_eq!(Afi::from_repr(3), None);
+        assert_eq!(Safi::from_repr(2), Some(Safi::Multicast));
+        assert_eq!(Safi::Multicast.to_string(), "MULTICAST");
+        assert_eq!(serde_json::to_value(Safi::MplsLabel).unwrap(), "MPLS_LABEL");
+    }
+}
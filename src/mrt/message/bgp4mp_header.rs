@@ -0,0 +1,95 @@
+use super::Afi;
+use crate::mrt::Error;
+use byteorder::{BigEndian, ReadBytesExt};
+use serde::Serialize;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Peer header shared by the BGP4MP MESSAGE and STATE_CHANGE subtypes
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct Bgp4MpHeader {
+    pub peer_asn: u32,        // Autonomous System Number of the peer
+    pub local_asn: u32,       // Autonomous System Number of the collector
+    pub interface_index: u16, // Interface index of the session on the collector
+    pub afi: Afi,             // Address family of the peer and local IPs
+    pub peer_ip: IpAddr,      // IP address of the peer
+    pub local_ip: IpAddr,     // IP address of the collector
+}
+
+impl Bgp4MpHeader {
+    /// Read the peer header, `as4` selects 4-byte instead of 2-byte ASNs
+    pub fn from_reader<R: Read>(reader: &mut R, as4: bool) -> Result<Self, Error> {
+        let (peer_asn, local_asn) = if as4 {
+            (
+                reader.read_u32::<BigEndian>()?,
+                reader.read_u32::<BigEndian>()?,
+            )
+        } else {
+            (
+                reader.read_u16::<BigEndian>()? as u32,
+                reader.read_u16::<BigEndian>()? as u32,
+            )
+        };
+        let interface_index = reader.read_u16::<BigEndian>()?;
+        let afi = reader.read_u16::<BigEndian>()?;
+        let afi = Afi::from_repr(afi).ok_or(Error::BadAfi(afi))?;
+        let (peer_ip, local_ip) = match afi {
+            Afi::Ipv4 => (
+                IpAddr::V4(Ipv4Addr::from_bits(reader.read_u32::<BigEndian>()?)),
+                IpAddr::V4(Ipv4Addr::from_bits(reader.read_u32::<BigEndian>()?)),
+            ),
+            Afi::Ipv6 => (
+                IpAddr::V6(Ipv6Addr::from_bits(reader.read_u128::<BigEndian>()?)),
+                IpAddr::V6(Ipv6Addr::from_bits(reader.read_u128::<BigEndian>()?)),
+            ),
+        };
+        Ok(Bgp4MpHeader {
+            peer_asn,
+            local_asn,
+            interface_index,
+            afi,
+            peer_ip,
+            local_ip,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reading_bgp4mp_header() {
+        let mut cursor = Cursor::new(vec![
+            0xfd, 0xe8, // peer AS 65000
+            0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ]);
+        let header = Bgp4MpHeader::from_reader(&mut cursor, false).unwrap();
+        assert_eq!(header.peer_asn, 65000);
+        assert_eq!(header.local_asn, 65001);
+        assert_eq!(header.afi, Afi::Ipv4);
+        assert_eq!(header.peer_ip, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(header.local_ip, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)));
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_header_as4_ipv6() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x01, 0x00, 0x00, // peer AS 65536
+            0x00, 0x00, 0xfd, 0xe9, // local AS 65001
+            0x00, 0x03, // interface index
+            0x00, 0x02, // AFI IPv6
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, // peer IP
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, // local IP
+        ]);
+        let header = Bgp4MpHeader::from_reader(&mut cursor, true).unwrap();
+        assert_eq!(header.peer_asn, 65536);
+        assert_eq!(header.interface_index, 3);
+        assert_eq!(header.peer_ip, "2001:db8::1".parse::<IpAddr>().unwrap());
+    }
+}
@@ -0,0 +1,698 @@
+use super::bgp_attribute::{BgpAttributeError, BgpAttributeType, ErrorAction};
+use super::{
+    Afi, Bgp4MpHeader, BgpAttributes, CsvRecord, MalformedRoutes, Nlri, Prefix, Safi, WriteRecords,
+};
+use crate::mrt::{Error, MRTHeader, MRTSubType};
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::fmt::{self, Display};
+use std::io::{Read, Write, copy, sink};
+use strum_macros::{Display, FromRepr};
+
+/// Size of the BGP message header: marker, length and type
+const BGP_HEADER_LENGTH: u16 = 19;
+
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u8)]
+pub enum BgpMessageType {
+    Open = 1,
+    Update = 2,
+    Notification = 3,
+    Keepalive = 4,
+    RouteRefresh = 5,
+}
+
+/// Content of a BGP UPDATE message
+#[derive(Debug, Serialize)]
+pub struct BgpUpdate {
+    pub withdrawn_routes: Vec<Prefix>,
+    pub attributes: BgpAttributes,
+    pub announced_routes: Vec<Prefix>,
+}
+
+/// BGP4MP MESSAGE and MESSAGE_AS4 records
+#[derive(Debug, Serialize)]
+pub struct Bgp4MpMessage {
+    time: DateTime<chrono::Utc>,
+    #[serde(skip)]
+    as4: bool, // MESSAGE_AS4 subtype, ASNs are 4 bytes long
+    #[serde(skip)]
+    extended_timestamp: bool, // BGP4MP_ET type, time has microseconds
+    header: Bgp4MpHeader,
+    message_type: BgpMessageType,
+    update: Option<BgpUpdate>, // Only set for UPDATE messages
+}
+
+/// One JSON record, announcements and withdrawals of an UPDATE are written
+/// as separate records
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+struct JsonRecord<'a> {
+    time: DateTime<chrono::Utc>,
+    #[serde(rename = "type")]
+    record_type: String,
+    #[serde(flatten)]
+    header: &'a Bgp4MpHeader,
+    #[serde(flatten)]
+    attributes: Option<&'a BgpAttributes>,
+    prefixes: Option<&'a Vec<Prefix>>,
+}
+
+impl BgpUpdate {
+    /// Read the body of an UPDATE message of `length` bytes (BGP header
+    /// excluded), `as4` tells whether the session uses 4-byte ASNs
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16, as4: bool) -> Result<Self, Error> {
+        let withdrawn_length = reader.read_u16::<BigEndian>()?;
+        let mut withdrawn_routes =
+            Prefix::list_from_reader(reader, Afi::Ipv4, withdrawn_length.into())?;
+        let attribute_length = reader.read_u16::<BigEndian>()?;
+        let mut attributes = BgpAttributes::from_reader(reader, attribute_length, as4)?;
+        // The NLRI takes whatever is left of the message
+        let nlri_length = length
+            .checked_sub(4)
+            .and_then(|length| length.checked_sub(withdrawn_length))
+            .and_then(|length| length.checked_sub(attribute_length))
+            .ok_or(Error::BadBgpMessageLength(length))?;
+        let mut announced_routes = Prefix::list_from_reader(reader, Afi::Ipv4, nlri_length.into())?;
+        // The unicast prefixes carried by MP_REACH_NLRI/MP_UNREACH_NLRI (e.g.
+        // IPv6 unicast) are reported like the IPv4 ones, the NLRI of the other
+        // SAFIs stay attached to their attribute
+        let unicast = Safi::Unicast as u8;
+        if let Some(mp_reach) = &mut attributes.bgp_mp_reach_nlri
+            && mp_reach.safi == Some(unicast)
+        {
+            mp_reach.nlri.retain(|nlri| match nlri {
+                Nlri::Prefix { prefix } => {
+                    announced_routes.push(*prefix);
+                    false
+                }
+                _ => true,
+            });
+        }
+        if let Some(mp_unreach) = &mut attributes.bgp_mp_unreach_nlri
+            && mp_unreach.safi == unicast
+        {
+            mp_unreach.withdrawn_routes.retain(|nlri| match nlri {
+                Nlri::Prefix { prefix } => {
+                    withdrawn_routes.push(*prefix);
+                    false
+                }
+                _ => true,
+            });
+        }
+        // An UPDATE announcing routes must carry the well-known mandatory
+        // attributes, NEXT_HOP only when there are IPv4 routes in the NLRI
+        // field (RFC 7606 section 3.d)
+        if nlri_length > 0 || attributes.bgp_mp_reach_nlri.is_some() {
+            let mut mandatory = vec![BgpAttributeType::Origin, BgpAttributeType::AsPath];
+            if nlri_length > 0 {
+                mandatory.push(BgpAttributeType::NextHop);
+            }
+            for attribute_type in mandatory {
+                let code = attribute_type as u8;
+                if !attributes.attribute_flags.iter().any(|(c, _)| *c == code) {
+                    attributes
+                        .attribute_errors
+                        .push(BgpAttributeError::new(code, Error::MissingAttribute(code)));
+                }
+            }
+        }
+        Ok(BgpUpdate {
+            withdrawn_routes,
+            attributes,
+            announced_routes,
+        })
+    }
+}
+
+impl BgpUpdate {
+    /// Whether MP_REACH_NLRI or MP_UNREACH_NLRI still hold routes, those of
+    /// a SAFI other than unicast
+    pub fn has_mp_routes(&self) -> bool {
+        let attributes = &self.attributes;
+        attributes
+            .bgp_mp_reach_nlri
+            .as_ref()
+            .is_some_and(|mp_reach| !mp_reach.nlri.is_empty())
+            || attributes
+                .bgp_mp_unreach_nlri
+                .as_ref()
+                .is_some_and(|mp_unreach| !mp_unreach.withdrawn_routes.is_empty())
+    }
+}
+
+impl Bgp4MpMessage {
+    pub fn from_reader<R: Read>(reader: &mut R, mrt_header: &MRTHeader) -> Result<Self, Error> {
+        let as4 = matches!(
+            mrt_header.mrt_subtype,
+            MRTSubType::Bgp4MpMessageAs4 | MRTSubType::Bgp4MpMessageAs4Local
+        );
+        let header = Bgp4MpHeader::from_reader(reader, as4)?;
+        // BGP message header
+        let mut marker = [0u8; 16];
+        reader.read_exact(&mut marker)?;
+        let length = reader.read_u16::<BigEndian>()?;
+        let length = length
+            .checked_sub(BGP_HEADER_LENGTH)
+            .ok_or(Error::BadBgpMessageLength(length))?;
+        let message_type = reader.read_u8()?;
+        let message_type = BgpMessageType::from_repr(message_type)
+            .ok_or(Error::BadBgpMessageType(message_type))?;
+        let update = match message_type {
+            BgpMessageType::Update => Some(BgpUpdate::from_reader(reader, length, as4)?),
+            _ => {
+                // skip the body of the other messages
+                copy(&mut reader.take(length.into()), &mut sink())?;
+                None
+            }
+        };
+        Ok(Bgp4MpMessage {
+            time: mrt_header.ts,
+            as4,
+            extended_timestamp: mrt_header.mrt_type.is_extended_timestamp(),
+            header,
+            message_type,
+            update,
+        })
+    }
+
+    fn subtype_name(&self) -> &'static str {
+        if self.as4 { "MESSAGE_AS4" } else { "MESSAGE" }
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    /// Whether the ASNs of the record are 4 bytes long
+    pub fn is_as4(&self) -> bool {
+        self.as4
+    }
+
+    pub fn header(&self) -> &Bgp4MpHeader {
+        &self.header
+    }
+
+    pub fn message_type(&self) -> BgpMessageType {
+        self.message_type
+    }
+
+    /// Content of the message, only for UPDATE messages
+    pub fn update(&self) -> Option<&BgpUpdate> {
+        self.update.as_ref()
+    }
+
+    pub fn into_update(self) -> Option<BgpUpdate> {
+        self.update
+    }
+
+    fn json_records(&self) -> Vec<JsonRecord<'_>> {
+        let record = |record_type: &str, attributes, prefixes| JsonRecord {
+            time: self.time,
+            record_type: record_type.to_string(),
+            header: &self.header,
+            attributes,
+            prefixes,
+        };
+        match &self.update {
+            None => vec![record(&self.message_type.to_string(), None, None)],
+            Some(update) => {
+                let mut records = Vec::new();
+                if !update.withdrawn_routes.is_empty() {
+                    records.push(record("WITHDRAW", None, Some(&update.withdrawn_routes)));
+                }
+                if !update.announced_routes.is_empty() {
+                    records.push(record(
+                        "ANNOUNCE",
+                        Some(&update.attributes),
+                        Some(&update.announced_routes),
+                    ));
+                } else if update.withdrawn_routes.is_empty() || update.has_mp_routes() {
+                    // End-of-RIB, or routes only found in the MP attributes
+                    records.push(record("UPDATE", Some(&update.attributes), None));
+                }
+                records
+            }
+        }
+    }
+
+    fn fmt_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.extended_timestamp {
+            writeln!(f, "TIME: {}", self.time.format("%Y-%m-%d %H:%M:%S%.6f"))?;
+        } else {
+            writeln!(f, "TIME: {}", self.time.format("%Y-%m-%d %H:%M:%S"))?;
+        }
+        writeln!(
+            f,
+            "TYPE: {}/{}/{}",
+            if self.extended_timestamp {
+                "BGP4MP_ET"
+            } else {
+                "BGP4MP"
+            },
+            self.subtype_name(),
+            self.message_type
+        )?;
+        writeln!(
+            f,
+            "FROM: {} AS {}",
+            self.header.peer_ip, self.header.peer_asn
+        )?;
+        writeln!(
+            f,
+            "TO: {} AS {}",
+            self.header.local_ip, self.header.local_asn
+        )
+    }
+}
+
+impl WriteRecords for Bgp4MpMessage {
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let Some(update) = &self.update else {
+            return Ok(());
+        };
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(b'|')
+            .has_headers(false)
+            .from_writer(writer);
+        let record =
+            |entry_type: &str, prefix: &Prefix, attributes: Option<&BgpAttributes>| CsvRecord {
+                record_type: "BGP4MP".to_string(),
+                datetime: self.time,
+                entry_type: entry_type.to_string(),
+                peer_ip: Some(self.header.peer_ip),
+                peer_asn: Some(self.header.peer_asn),
+                prefix_with_len: prefix.to_string(),
+                as_path: attributes
+                    .map(|attributes| attributes.as_path_string())
+                    .unwrap_or_default(),
+                bgp_origin: attributes
+                    .map(|attributes| attributes.origin_string())
+                    .unwrap_or_default(),
+                // Only the unicast routes are taken out of their attribute
+                safi: Safi::Unicast.to_string(),
+                path_id: None,
+                local_pref: attributes
+                    .and_then(|attributes| attributes.bgp_local_pref.as_ref())
+                    .map(|pref| pref.0),
+                originator_id: attributes
+                    .and_then(|attributes| attributes.bgp_originator_id.as_ref())
+                    .map(|id| id.0),
+                cluster_list: attributes
+                    .map(|attributes| attributes.cluster_list_string())
+                    .unwrap_or_default(),
+                atomic_aggregate: attributes
+                    .map(|attributes| attributes.atomic_aggregate_string())
+                    .unwrap_or_default(),
+                otc: attributes
+                    .and_then(|attributes| attributes.bgp_otc.as_ref())
+                    .map(|otc| otc.0),
+                error_action: attributes
+                    .and_then(|attributes| attributes.error_action)
+                    .map(|action| action.to_string())
+                    .unwrap_or_default(),
+                peer_latitude: None,
+                peer_longitude: None,
+            };
+        for prefix in &update.withdrawn_routes {
+            csv_writer.serialize(record("W", prefix, None))?;
+        }
+        for prefix in &update.announced_routes {
+            csv_writer.serialize(record("A", prefix, Some(&update.attributes)))?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for record in self.json_records() {
+            let json = serde_json::to_string_pretty(&record)?;
+            writeln!(writer, "{}", json)?;
+        }
+        Ok(())
+    }
+}
+
+impl MalformedRoutes for Bgp4MpMessage {
+    fn mark_malformed(&mut self) {
+        if let Some(update) = &mut self.update {
+            update.attributes.mark_error_action();
+        }
+    }
+
+    /// The announced routes of a treat-as-withdraw UPDATE become withdrawals
+    fn drop_malformed(&mut self) -> bool {
+        let Some(update) = &mut self.update else {
+            return true;
+        };
+        match update.attributes.most_severe_error() {
+            Some(ErrorAction::SessionReset) => false,
+            Some(ErrorAction::TreatAsWithdraw) => {
+                let announced_routes = std::mem::take(&mut update.announced_routes);
+                update.withdrawn_routes.extend(announced_routes);
+                update.attributes.bgp_mp_reach_nlri = None;
+                !update.withdrawn_routes.is_empty()
+            }
+            _ => update.attributes.drop_malformed(),
+        }
+    }
+}
+
+impl Display for Bgp4MpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(update) = &self.update else {
+            return self.fmt_header(f);
+        };
+        if !update.withdrawn_routes.is_empty() {
+            self.fmt_header(f)?;
+            writeln!(f, "WITHDRAW")?;
+            for prefix in &update.withdrawn_routes {
+                writeln!(f, "  {}", prefix)?;
+            }
+            writeln!(f)?;
+        }
+        if !update.announced_routes.is_empty()
+            || update.withdrawn_routes.is_empty()
+            || update.has_mp_routes()
+        {
+            self.fmt_header(f)?;
+            if f.alternate() {
+                write!(f, "{:#}", update.attributes)?;
+            } else {
+                write!(f, "{}", update.attributes)?;
+            }
+            if !update.announced_routes.is_empty() {
+                writeln!(f, "ANNOUNCE")?;
+                for prefix in &update.announced_routes {
+                    writeln!(f, "  {}", prefix)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::MRTType;
+    use std::io::Cursor;
+
+    fn mrt_header(
+        mrt_type: MRTType,
+        mrt_subtype: MRTSubType,
+        ts: DateTime<chrono::Utc>,
+    ) -> MRTHeader {
+        MRTHeader {
+            ts,
+            mrt_type,
+            mrt_subtype,
+            length: 0,
+        }
+    }
+
+    fn update_message() -> Vec<u8> {
+        let mut message = vec![
+            0x00, 0x00, 0xfd, 0xe8, // peer AS 65000
+            0x00, 0x00, 0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        message.extend_from_slice(&[0xff; 16]); // marker
+        message.extend_from_slice(&[
+            0x00, 0x31, // length 49
+            0x02, // type UPDATE
+            0x00, 0x02, // withdrawn routes length
+            0x08, 0x0a, // 10.0.0.0/8
+            0x00, 0x14, // path attribute length 20
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0x40, 0x02, 0x06, // as path length 6
+            0x02, 0x01, 0x00, 0x00, 0xfd, 0xe8, // AS_SEQUENCE 65000
+            0x40, 0x03, 0x04, 192, 0, 2, 1, // next hop
+            0x18, 0xc6, 0x33, 0x64, // 198.51.100.0/24
+        ]);
+        message
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_update() {
+        let mut cursor = Cursor::new(update_message());
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessageAs4, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        assert_eq!(message.message_type, BgpMessageType::Update);
+        let update = message.update.as_ref().unwrap();
+        assert_eq!(update.withdrawn_routes.len(), 1);
+        assert_eq!(update.withdrawn_routes[0].to_string(), "10.0.0.0/8");
+        assert_eq!(update.announced_routes.len(), 1);
+        assert_eq!(update.announced_routes[0].to_string(), "198.51.100.0/24");
+        assert_eq!(update.attributes.origin_string(), "IGP");
+        // the whole message is consumed
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+
+        let records = message.json_records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].record_type, "WITHDRAW");
+        assert_eq!(records[1].record_type, "ANNOUNCE");
+
+        let mut csv = Vec::new();
+        message.write_csv_records(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("BGP4MP|1970-01-01T00:00:00Z|W|192.0.2.1|65000|10.0.0.0/8|"));
+        assert!(
+            lines[1].starts_with("BGP4MP|1970-01-01T00:00:00Z|A|192.0.2.1|65000|198.51.100.0/24|")
+        );
+    }
+
+    #[test]
+    fn test_missing_next_hop_is_treated_as_withdraw() {
+        let mut message = update_message();
+        message[59] = 0x63; // the NEXT_HOP becomes an unknown attribute
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessageAs4, time);
+        let mut message =
+            Bgp4MpMessage::from_reader(&mut Cursor::new(message), &mrt_header).unwrap();
+        let attributes = &message.update.as_ref().unwrap().attributes;
+        assert_eq!(attributes.attribute_errors[0].type_code, 3);
+        assert_eq!(
+            attributes.most_severe_error(),
+            Some(ErrorAction::TreatAsWithdraw)
+        );
+
+        message.mark_malformed();
+        let mut csv = Vec::new();
+        message.write_csv_records(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert!(lines[0].ends_with("|"));
+        assert!(lines[1].ends_with("|TREAT_AS_WITHDRAW||"));
+
+        assert!(message.drop_malformed());
+        let update = message.update.as_ref().unwrap();
+        assert!(update.announced_routes.is_empty());
+        assert_eq!(update.withdrawn_routes[1].to_string(), "198.51.100.0/24");
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_keepalive() {
+        let mut message = vec![
+            0xfd, 0xe8, // peer AS 65000
+            0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        message.extend_from_slice(&[0xff; 16]); // marker
+        message.extend_from_slice(&[0x00, 0x13, 0x04]); // KEEPALIVE
+        let mut cursor = Cursor::new(message);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessage, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        assert_eq!(message.message_type, BgpMessageType::Keepalive);
+        assert!(message.update.is_none());
+        assert_eq!(message.json_records()[0].record_type, "KEEPALIVE");
+        assert_eq!(BgpMessageType::RouteRefresh.to_string(), "ROUTE_REFRESH");
+        assert_eq!(
+            message.to_string(),
+            "TIME: 1970-01-01 00:00:00\nTYPE: BGP4MP/MESSAGE/KEEPALIVE\n\
+             FROM: 192.0.2.1 AS 65000\nTO: 192.0.2.2 AS 65001\n"
+        );
+    }
+
+    #[test]
+    fn test_bgp4mp_et_microseconds() {
+        let mut cursor = Cursor::new(update_message());
+        let time = DateTime::from_timestamp(0, 123_456_000).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4MpEt, MRTSubType::Bgp4MpMessageAs4, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        assert!(
+            message.to_string().starts_with(
+                "TIME: 1970-01-01 00:00:00.123456\nTYPE: BGP4MP_ET/MESSAGE_AS4/UPDATE\n"
+            )
+        );
+        let mut csv = Vec::new();
+        message.write_csv_records(&mut csv).unwrap();
+        assert!(
+            String::from_utf8(csv)
+                .unwrap()
+                .starts_with("BGP4MP|1970-01-01T00:00:00.123456Z|W|")
+        );
+        let records = message.json_records();
+        assert_eq!(
+            serde_json::to_value(&records[0]).unwrap()["time"],
+            "1970-01-01T00:00:00.123456Z"
+        );
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_ipv6_update() {
+        let mut message = vec![
+            0x00, 0x00, 0xfd, 0xe8, // peer AS 65000
+            0x00, 0x00, 0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        message.extend_from_slice(&[0xff; 16]); // marker
+        message.extend_from_slice(&[
+            0x00, 0x41, // length 65
+            0x02, // type UPDATE
+            0x00, 0x00, // withdrawn routes length
+            0x00, 0x2a, // path attribute length 42
+            0x80, 0x0f, 0x08, // MP_UNREACH_NLRI length 8
+            0x00, 0x02, 0x02, // IPv6 multicast
+            0x20, 0x20, 0x01, 0x0d, 0xb8, // 2001:db8::/32
+            0x80, 0x0e, 0x1c, // MP_REACH_NLRI length 28
+            0x00, 0x02, 0x01, // IPv6 unicast
+            0x10, // next hop length
+        ]);
+        message.extend_from_slice(
+            &"2001:db8::1"
+                .parse::<std::net::Ipv6Addr>()
+                .unwrap()
+                .octets(),
+        );
+        message.extend_from_slice(&[
+            0x00, // reserved
+            0x30, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x01, // 2001:db8:1::/48
+        ]);
+        let mut cursor = Cursor::new(message);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessageAs4, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        let update = message.update.as_ref().unwrap();
+        // multicast routes are left in their attribute
+        assert!(update.withdrawn_routes.is_empty());
+        let mp_unreach = update.attributes.bgp_mp_unreach_nlri.as_ref().unwrap();
+        assert_eq!(
+            mp_unreach.withdrawn_routes[0].prefix().unwrap().to_string(),
+            "2001:db8::/32"
+        );
+        assert_eq!(update.announced_routes[0].to_string(), "2001:db8:1::/48");
+        let mp_reach = update.attributes.bgp_mp_reach_nlri.as_ref().unwrap();
+        assert_eq!(mp_reach.next_hop, Some("2001:db8::1".parse().unwrap()));
+        assert!(mp_reach.nlri.is_empty());
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+    }
+
+    #[test]
+    fn test_vpn_only_update_in_json() {
+        let mut message = vec![
+            0x00, 0x00, 0xfd, 0xe8, // peer AS 65000
+            0x00, 0x00, 0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        message.extend_from_slice(&[0xff; 16]); // marker
+        message.extend_from_slice(&[
+            0x00, 0x45, // length 69
+            0x02, // type UPDATE
+            0x00, 0x00, // withdrawn routes length
+            0x00, 0x2e, // path attribute length 46
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0x40, 0x02, 0x06, // as path length 6
+            0x02, 0x01, 0x00, 0x00, 0xfd, 0xe8, // AS_SEQUENCE 65000
+            0x80, 0x0e, 0x1e, // MP_REACH_NLRI length 30
+            0x00, 0x01, 0x80, // IPv4 MPLS VPN
+            0x0c, // next hop length
+            0, 0, 0, 0, 0, 0, 0, 0, 192, 0, 2, 1,    // next hop, RD 0
+            0x00, // reserved
+            0x60, 0x00, 0x00, 0x11, // length 96, label 1
+            0x00, 0x00, 0xfd, 0xe8, 0x00, 0x00, 0x00, 0x01, // RD 65000:1
+            0x0a, // 10.0.0.0/8
+        ]);
+        let mut cursor = Cursor::new(message);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessageAs4, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        let update = message.update.as_ref().unwrap();
+        assert!(update.announced_routes.is_empty());
+        assert!(update.has_mp_routes());
+        let records = message.json_records();
+        assert_eq!(records.len(), 1);
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["type"], "UPDATE");
+        assert_eq!(json["bgp_mp_reach_nlri"]["nlri"][0]["type"], "vpn");
+        assert!(message.to_string().contains("MP_REACH_NLRI(IPV4_MPLS_VPN)"));
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_as4_path_merge() {
+        let mut message = vec![
+            0xfd, 0xe8, // peer AS 65000
+            0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        message.extend_from_slice(&[0xff; 16]); // marker
+        message.extend_from_slice(&[
+            0x00, 0x4b, // length 75
+            0x02, // type UPDATE
+            0x00, 0x00, // withdrawn routes length
+            0x00, 0x30, // path attribute length 48
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0x40, 0x02, 0x08, // as path length 8
+            0x02, 0x03, // AS_SEQUENCE
+            0xfd, 0xe8, // 65000
+            0x5b, 0xa0, // AS_TRANS
+            0x5b, 0xa0, // AS_TRANS
+            0xc0, 0x07, 0x06, // aggregator length 6
+            0x5b, 0xa0, 192, 0, 2, 9, // AS_TRANS 192.0.2.9
+            0xc0, 0x11, 0x0a, // AS4_PATH length 10
+            0x02, 0x02, // AS_SEQUENCE
+            0x00, 0x03, 0x0d, 0x40, // 200000
+            0x00, 0x03, 0x0d, 0x41, // 200001
+            0xc0, 0x12, 0x08, // AS4_AGGREGATOR length 8
+            0x00, 0x03, 0x0d, 0x41, 192, 0, 2, 9, // 200001 192.0.2.9
+            0x18, 0xc6, 0x33, 0x64, // 198.51.100.0/24
+        ]);
+        let mut cursor = Cursor::new(message);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let mrt_header = mrt_header(MRTType::Bgp4Mp, MRTSubType::Bgp4MpMessage, time);
+        let message = Bgp4MpMessage::from_reader(&mut cursor, &mrt_header).unwrap();
+        let update = message.update.as_ref().unwrap();
+        assert_eq!(update.attributes.as_path_string(), "65000 200000 200001");
+        let as_path = update.attributes.bgp_as_path.as_ref().unwrap();
+        assert_eq!(as_path.origin_asn(), Some(200001));
+        assert_eq!(
+            update.attributes.bgp_aggregator.as_ref().unwrap().asn,
+            200001
+        );
+        assert_eq!(update.announced_routes[0].to_string(), "198.51.100.0/24");
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+    }
+}
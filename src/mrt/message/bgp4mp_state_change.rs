@@ -0,0 +1,222 @@
+use super::Bgp4MpHeader;
+use super::CsvRecord;
+use super::MalformedRoutes;
+use super::WriteRecords;
+use crate::mrt::{Error, MRTHeader, MRTSubType};
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use serde::Serialize;
+use std::fmt::{self, Display};
+use std::io::{Read, Write};
+use strum_macros::{Display, FromRepr};
+
+/// BGP finite state machine states (RFC 4271 section 8.2.2)
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u16)]
+pub enum BgpState {
+    Idle = 1,
+    Connect = 2,
+    Active = 3,
+    OpenSent = 4,
+    OpenConfirm = 5,
+    Established = 6,
+}
+
+/// BGP4MP STATE_CHANGE and STATE_CHANGE_AS4 records
+#[derive(Debug, Serialize)]
+pub struct Bgp4MpStateChange {
+    time: DateTime<chrono::Utc>,
+    #[serde(skip)]
+    as4: bool, // STATE_CHANGE_AS4 subtype, ASNs are 4 bytes long
+    #[serde(skip)]
+    extended_timestamp: bool, // BGP4MP_ET type, time has microseconds
+    #[serde(flatten)]
+    header: Bgp4MpHeader,
+    old_state: BgpState,
+    new_state: BgpState,
+}
+
+impl Bgp4MpStateChange {
+    pub fn from_reader<R: Read>(reader: &mut R, mrt_header: &MRTHeader) -> Result<Self, Error> {
+        let as4 = mrt_header.mrt_subtype == MRTSubType::Bgp4MpStateChangeAs4;
+        let header = Bgp4MpHeader::from_reader(reader, as4)?;
+        let old_state = reader.read_u16::<BigEndian>()?;
+        let old_state = BgpState::from_repr(old_state).ok_or(Error::BadBgpState(old_state))?;
+        let new_state = reader.read_u16::<BigEndian>()?;
+        let new_state = BgpState::from_repr(new_state).ok_or(Error::BadBgpState(new_state))?;
+        Ok(Bgp4MpStateChange {
+            time: mrt_header.ts,
+            as4,
+            extended_timestamp: mrt_header.mrt_type.is_extended_timestamp(),
+            header,
+            old_state,
+            new_state,
+        })
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    /// Whether the ASNs of the record are 4 bytes long
+    pub fn is_as4(&self) -> bool {
+        self.as4
+    }
+
+    pub fn header(&self) -> &Bgp4MpHeader {
+        &self.header
+    }
+
+    pub fn old_state(&self) -> BgpState {
+        self.old_state
+    }
+
+    pub fn new_state(&self) -> BgpState {
+        self.new_state
+    }
+
+    fn subtype_name(&self) -> &'static str {
+        if self.as4 {
+            "STATE_CHANGE_AS4"
+        } else {
+            "STATE_CHANGE"
+        }
+    }
+}
+
+impl WriteRecords for Bgp4MpStateChange {
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(b'|')
+            .has_headers(false)
+            .from_writer(writer);
+        // bgpdump writes the state codes where the prefix and the AS path
+        // of the route lines go: `BGP4MP|time|STATE|ip|asn|old|new`
+        csv_writer.serialize(CsvRecord {
+            record_type: "BGP4MP".to_string(),
+            datetime: self.time,
+            entry_type: "STATE".to_string(),
+            peer_ip: Some(self.header.peer_ip),
+            peer_asn: Some(self.header.peer_asn),
+            prefix_with_len: (self.old_state as u16).to_string(),
+            as_path: (self.new_state as u16).to_string(),
+            bgp_origin: String::new(),
+            safi: String::new(),
+            path_id: None,
+            local_pref: None,
+            originator_id: None,
+            cluster_list: String::new(),
+            atomic_aggregate: String::new(),
+            otc: None,
+            error_action: String::new(),
+            peer_latitude: None,
+            peer_longitude: None,
+        })?;
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        writeln!(writer, "{}", json)?;
+        Ok(())
+    }
+}
+
+/// A state change holds no route, there is nothing to mark or drop
+impl MalformedRoutes for Bgp4MpStateChange {
+    fn mark_malformed(&mut self) {}
+
+    fn drop_malformed(&mut self) -> bool {
+        true
+    }
+}
+
+impl Display for Bgp4MpStateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.extended_timestamp {
+            writeln!(f, "TIME: {}", self.time.format("%Y-%m-%d %H:%M:%S%.6f"))?;
+            writeln!(f, "TYPE: BGP4MP_ET/{}", self.subtype_name())?;
+        } else {
+            writeln!(f, "TIME: {}", self.time.format("%Y-%m-%d %H:%M:%S"))?;
+            writeln!(f, "TYPE: BGP4MP/{}", self.subtype_name())?;
+        }
+        writeln!(
+            f,
+            "PEER: {} AS {}",
+            self.header.peer_ip, self.header.peer_asn
+        )?;
+        writeln!(f, "STATE: {}/{}", self.old_state, self.new_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::MRTType;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reading_bgp4mp_state_change() {
+        let mut cursor = Cursor::new(vec![
+            0xfd, 0xe8, // peer AS 65000
+            0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+            0x00, 0x06, // old state Established
+            0x00, 0x01, // new state Idle
+        ]);
+        let mrt_header = MRTHeader {
+            ts: DateTime::from_timestamp(0, 0).unwrap(),
+            mrt_type: MRTType::Bgp4Mp,
+            mrt_subtype: MRTSubType::Bgp4MpStateChange,
+            length: 0,
+        };
+        let state_change = Bgp4MpStateChange::from_reader(&mut cursor, &mrt_header).unwrap();
+        assert_eq!(state_change.old_state, BgpState::Established);
+        assert_eq!(state_change.new_state, BgpState::Idle);
+        assert_eq!(
+            state_change.to_string(),
+            "TIME: 1970-01-01 00:00:00\nTYPE: BGP4MP/STATE_CHANGE\n\
+             PEER: 192.0.2.1 AS 65000\nSTATE: ESTABLISHED/IDLE\n"
+        );
+
+        let mut csv = Vec::new();
+        state_change.write_csv_records(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "BGP4MP|1970-01-01T00:00:00Z|STATE|192.0.2.1|65000|6|1|||||||||||\n"
+        );
+
+        let json = serde_json::to_value(&state_change).unwrap();
+        assert_eq!(json["old_state"], "ESTABLISHED");
+        assert_eq!(json["new_state"], "IDLE");
+        assert_eq!(json["peer_asn"], 65000);
+    }
+
+    #[test]
+    fn test_reading_bgp4mp_bad_state() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0xfd, 0xe8, // peer AS 65000
+            0x00, 0x00, 0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+            0x00, 0x07, // old state
+            0x00, 0x01, // new state Idle
+        ]);
+        let mrt_header = MRTHeader {
+            ts: DateTime::from_timestamp(0, 0).unwrap(),
+            mrt_type: MRTType::Bgp4MpEt,
+            mrt_subtype: MRTSubType::Bgp4MpStateChangeAs4,
+            length: 0,
+        };
+        let state_change = Bgp4MpStateChange::from_reader(&mut cursor, &mrt_header);
+        assert!(matches!(state_change.unwrap_err(), Error::BadBgpState(7)));
+    }
+}
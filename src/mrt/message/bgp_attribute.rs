@@ -1,9 +1,18 @@
+use super::{Afi, ExtendedCommunity, Nlri, Safi};
 use crate::mrt::Error;
 use byteorder::{BigEndian, ReadBytesExt};
 use serde::Serialize;
-use std::{fmt, io::Read, net::Ipv4Addr};
+use serde_with::{DisplayFromStr, hex::Hex, serde_as, skip_serializing_none};
+use std::{
+    fmt,
+    io::{Cursor, Read, copy, sink},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 use strum_macros::{Display, FromRepr};
 
+/// AS number standing for a 4-byte ASN on 2-byte sessions (RFC 6793)
+pub const AS_TRANS: u32 = 23456;
+
 #[derive(Debug, FromRepr, Serialize)]
 #[repr(u8)]
 pub enum BgpOriginType {
@@ -13,7 +22,6 @@ pub enum BgpOriginType {
 }
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpMultiExitDisc(pub u32);
 
 #[derive(Debug, FromRepr, Display, Serialize, PartialEq)]
@@ -23,84 +31,796 @@ pub enum BgpAttributeType {
     AsPath = 2,
     NextHop = 3,
     MultiExitDisc = 4,
+    LocalPref = 5,
     AtomicAggregate = 6,
     Aggregator = 7,
     Community = 8,
+    OriginatorId = 9,
+    ClusterList = 10,
+    MpReachNlri = 14,
+    MpUnreachNlri = 15,
+    ExtendedCommunity = 16,
+    As4Path = 17,
+    As4Aggregator = 18,
+    Ipv6ExtendedCommunity = 25,
     LargeCommunity = 32,
     Otc = 35,
     BfdDiscriminator = 38,
 }
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpNextHop(pub Ipv4Addr);
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
+pub struct BgpLocalPref(pub u32);
+
+/// BGP identifier of the route reflection originator (RFC 4456)
+#[derive(Debug, Serialize)]
+pub struct BgpOriginatorId(pub Ipv4Addr);
+
+/// ASN of the Only-To-Customer attribute (RFC 9234)
+#[derive(Debug, Serialize)]
+pub struct BgpOtc(pub u32);
+
+/// Cluster IDs of the route reflectors the route went through (RFC 4456)
+#[derive(Debug, Serialize)]
+pub struct BgpClusterList(pub Vec<Ipv4Addr>);
+
+#[derive(Debug, Serialize)]
 pub struct BgpOrigin(pub BgpOriginType);
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpCommunity(pub Vec<(u16, u16)>);
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpLargeCommunity(pub Vec<(u32, u32, u32)>);
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
+pub struct BgpExtendedCommunity(pub Vec<ExtendedCommunity>);
+
+#[derive(Debug, Serialize)]
+pub struct BgpIpv6ExtendedCommunity(pub Vec<ExtendedCommunity>);
+
+/// AS_PATH segment types (RFC 4271 and RFC 5065 for confederations)
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum AsPathSegmentType {
+    #[serde(rename = "AS_SET")]
+    #[strum(serialize = "AS_SET")]
+    Set = 1,
+    #[serde(rename = "AS_SEQUENCE")]
+    #[strum(serialize = "AS_SEQUENCE")]
+    Sequence = 2,
+    #[serde(rename = "AS_CONFED_SEQUENCE")]
+    #[strum(serialize = "AS_CONFED_SEQUENCE")]
+    ConfedSequence = 3,
+    #[serde(rename = "AS_CONFED_SET")]
+    #[strum(serialize = "AS_CONFED_SET")]
+    ConfedSet = 4,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct AsPathSegment {
+    pub segment_type: AsPathSegmentType,
+    pub asns: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
 pub struct BgpAsPath {
-    pub segment_type: u8,
-    pub segments: Vec<i32>,
+    pub segments: Vec<AsPathSegment>,
+}
+
+/// MP_REACH_NLRI (RFC 4760), `afi`, `safi` and `nlri` are absent from the
+/// abbreviated form used in TABLE_DUMP_V2 RIB entries (RFC 6396 section 4.3.4)
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct BgpMpReachNlri {
+    pub afi: Option<u16>,
+    pub safi: Option<u8>,
+    pub next_hop: Option<IpAddr>,              // Global next hop
+    pub next_hop_link_local: Option<Ipv6Addr>, // IPv6 link-local next hop
+    pub nlri: Vec<Nlri>,
+}
+
+/// MP_UNREACH_NLRI (RFC 4760)
+#[derive(Debug, Serialize)]
+pub struct BgpMpUnreachNlri {
+    pub afi: u16,
+    pub safi: u8,
+    pub withdrawn_routes: Vec<Nlri>,
 }
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpAggregator {
     pub asn: u32,
     pub ip: Ipv4Addr,
 }
 
+/// How a router handles a malformed attribute (RFC 7606 section 2), from the
+/// least to the most severe
+#[derive(Debug, Display, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorAction {
+    AttributeDiscard,
+    TreatAsWithdraw,
+    SessionReset,
+}
+
+/// An attribute that failed to decode, whose value length didn't match its
+/// content, or a missing well-known attribute
+#[derive(Debug, Serialize)]
+pub struct BgpAttributeError {
+    pub type_code: u8,
+    pub action: ErrorAction,
+    pub message: String,
+}
+
+/// Flags octet of a path attribute (RFC 4271 section 4.3)
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct BgpAttributeFlags {
+    pub optional: bool,
+    pub transitive: bool,
+    pub partial: bool,
+    pub extended_length: bool,
+}
+
+/// An attribute we don't decode, kept with its flags and raw value
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct BgpUnknownAttribute {
+    pub flags: u8,
+    pub type_code: u8,
+    #[serde_as(as = "Hex")]
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct BgpAttributeHeader {
     pub attribute_flag: u8,
-    pub attribute_type: BgpAttributeType,
+    pub attribute_code: u8,                       // Raw attribute type code
+    pub attribute_type: Option<BgpAttributeType>, // None for unknown attributes
     pub attribute_length: u16,
-    pub offset: u16,
+    pub offset: u32, // Header and value length, up to 4 + 0xffff
+}
+
+/// Path attributes of a route, shared by RIB entries and BGP UPDATE messages
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Default, Serialize)]
+pub struct BgpAttributes {
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub bgp_origin: Option<BgpOrigin>,
+    pub bgp_as_path: Option<BgpAsPath>,
+    pub bgp_next_hop: Option<BgpNextHop>,
+    pub bgp_community: Option<BgpCommunity>,
+    pub bgp_large_community: Option<BgpLargeCommunity>,
+    pub bgp_extended_community: Option<BgpExtendedCommunity>,
+    pub bgp_ipv6_extended_community: Option<BgpIpv6ExtendedCommunity>,
+    pub bgp_multi_exit_disc: Option<BgpMultiExitDisc>,
+    pub bgp_local_pref: Option<BgpLocalPref>,
+    pub bgp_originator_id: Option<BgpOriginatorId>,
+    pub bgp_cluster_list: Option<BgpClusterList>,
+    pub bgp_aggregator: Option<BgpAggregator>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub bgp_atomic_aggregate: bool,
+    pub bgp_otc: Option<BgpOtc>,
+    pub bgp_as4_path: Option<BgpAsPath>,
+    pub bgp_as4_aggregator: Option<BgpAggregator>,
+    pub bgp_mp_reach_nlri: Option<BgpMpReachNlri>,
+    pub bgp_mp_unreach_nlri: Option<BgpMpUnreachNlri>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bgp_unknown_attributes: Vec<BgpUnknownAttribute>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attribute_errors: Vec<BgpAttributeError>,
+    pub error_action: Option<ErrorAction>, // Most severe action, only set once marked
+    #[serde(skip)]
+    pub attribute_flags: Vec<(u8, BgpAttributeFlags)>, // Type code and flags, in wire order
+}
+
+impl ErrorAction {
+    /// Action for a malformed attribute of the given type (RFC 7606 section 7)
+    pub fn for_attribute(type_code: u8) -> Self {
+        match BgpAttributeType::from_repr(type_code) {
+            Some(
+                BgpAttributeType::Origin
+                | BgpAttributeType::AsPath
+                | BgpAttributeType::NextHop
+                | BgpAttributeType::MultiExitDisc
+                | BgpAttributeType::LocalPref
+                | BgpAttributeType::Community
+                | BgpAttributeType::OriginatorId
+                | BgpAttributeType::ClusterList
+                | BgpAttributeType::ExtendedCommunity
+                | BgpAttributeType::Ipv6ExtendedCommunity
+                | BgpAttributeType::LargeCommunity
+                | BgpAttributeType::Otc,
+            ) => ErrorAction::TreatAsWithdraw,
+            // The NLRI cannot be trusted, the whole session goes down
+            Some(BgpAttributeType::MpReachNlri | BgpAttributeType::MpUnreachNlri) => {
+                ErrorAction::SessionReset
+            }
+            _ => ErrorAction::AttributeDiscard,
+        }
+    }
+}
+
+impl BgpAttributeError {
+    pub fn new(type_code: u8, error: Error) -> Self {
+        BgpAttributeError {
+            type_code,
+            action: ErrorAction::for_attribute(type_code),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl BgpAttributeFlags {
+    pub fn from_bits(flags: u8) -> Self {
+        BgpAttributeFlags {
+            optional: flags & 0x80 != 0,
+            transitive: flags & 0x40 != 0,
+            partial: flags & 0x20 != 0,
+            extended_length: flags & 0x10 != 0,
+        }
+    }
+}
+
+impl fmt::Display for BgpAttributeFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.optional, "OPTIONAL"),
+            (self.transitive, "TRANSITIVE"),
+            (self.partial, "PARTIAL"),
+            (self.extended_length, "EXTENDED_LENGTH"),
+        ];
+        let flags = flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        if flags.is_empty() {
+            write!(f, "WELL_KNOWN")
+        } else {
+            write!(f, "{}", flags.join(" "))
+        }
+    }
 }
 
 impl BgpAttributeHeader {
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let is_extended_length = 0x10;
         let attribute_flag = reader.read_u8()?;
-        let attribute_type = reader.read_u8()?;
-        let attribute_length = if attribute_flag & is_extended_length == 0 {
-            reader.read_u8()? as u16
-        } else {
+        let attribute_code = reader.read_u8()?;
+        let extended_length = BgpAttributeFlags::from_bits(attribute_flag).extended_length;
+        let attribute_length = if extended_length {
             reader.read_u16::<BigEndian>()?
+        } else {
+            reader.read_u8()? as u16
         };
-        let offset = if attribute_flag & is_extended_length == 0 {
-            3 + attribute_length
+        let offset = if extended_length {
+            4 + u32::from(attribute_length)
         } else {
-            4 + attribute_length
+            3 + u32::from(attribute_length)
         };
-        // parse bgp attribute
-        let attribute_type = BgpAttributeType::from_repr(attribute_type)
-            .ok_or(Error::ParsingBgpAttribute(attribute_type))?;
-
         Ok(BgpAttributeHeader {
             attribute_flag,
-            attribute_type,
+            attribute_code,
+            attribute_type: BgpAttributeType::from_repr(attribute_code),
             attribute_length,
             offset,
         })
     }
 }
 
+impl BgpAttributes {
+    /// Read `attribute_length` bytes of path attributes, `as4` tells whether
+    /// AS_PATH holds 4-byte ASNs. On 2-byte sessions the true path and
+    /// aggregator are rebuilt from AS4_PATH and AS4_AGGREGATOR
+    pub fn from_reader<R: Read>(
+        reader: &mut R,
+        attribute_length: u16,
+        as4: bool,
+    ) -> Result<Self, Error> {
+        let mut attributes = BgpAttributes::default();
+        let mut reader = reader.take(attribute_length.into());
+        // loop over all attributes
+        while reader.limit() > 0 {
+            let header = BgpAttributeHeader::from_reader(&mut reader)?;
+            attributes.attribute_flags.push((
+                header.attribute_code,
+                BgpAttributeFlags::from_bits(header.attribute_flag),
+            ));
+            // Decoders only see the attribute value, so that a malformed
+            // attribute cannot eat into the next one
+            let mut attribute_reader = reader.by_ref().take(header.attribute_length.into());
+            let result = attributes.read_attribute(&mut attribute_reader, &header, as4);
+            // Always resume at the attribute boundary
+            let unread = copy(&mut attribute_reader, &mut sink())?;
+            let result = match result {
+                Ok(()) if unread > 0 || attribute_reader.limit() > 0 => Err(
+                    Error::BadAttributeLength(header.attribute_code, header.attribute_length),
+                ),
+                result => result,
+            };
+            if let Err(error) = result {
+                attributes
+                    .attribute_errors
+                    .push(BgpAttributeError::new(header.attribute_code, error));
+            }
+        }
+        // AS4_PATH and AS4_AGGREGATOR are only meaningful on 2-byte sessions
+        if !as4 {
+            attributes.merge_as4_attributes();
+        }
+        Ok(attributes)
+    }
+
+    /// Most severe RFC 7606 action required by the attribute errors
+    pub fn most_severe_error(&self) -> Option<ErrorAction> {
+        self.attribute_errors.iter().map(|error| error.action).max()
+    }
+
+    /// Record the most severe action in `error_action` so that it shows up
+    /// in the output
+    pub fn mark_error_action(&mut self) {
+        self.error_action = self.most_severe_error();
+    }
+
+    /// Apply the RFC 7606 actions like a router would: returns false when
+    /// the route must be withdrawn, otherwise drops the discarded attributes
+    pub fn drop_malformed(&mut self) -> bool {
+        if self
+            .most_severe_error()
+            .is_some_and(|action| action >= ErrorAction::TreatAsWithdraw)
+        {
+            return false;
+        }
+        let codes = self
+            .attribute_errors
+            .iter()
+            .map(|error| error.type_code)
+            .collect::<Vec<_>>();
+        for code in codes {
+            self.discard_attribute(code);
+        }
+        true
+    }
+
+    /// Forget the value of an attribute
+    fn discard_attribute(&mut self, type_code: u8) {
+        match BgpAttributeType::from_repr(type_code) {
+            Some(BgpAttributeType::Origin) => self.bgp_origin = None,
+            Some(BgpAttributeType::AsPath) => self.bgp_as_path = None,
+            Some(BgpAttributeType::NextHop) => self.bgp_next_hop = None,
+            Some(BgpAttributeType::MultiExitDisc) => self.bgp_multi_exit_disc = None,
+            Some(BgpAttributeType::LocalPref) => self.bgp_local_pref = None,
+            Some(BgpAttributeType::AtomicAggregate) => self.bgp_atomic_aggregate = false,
+            Some(BgpAttributeType::Aggregator) => self.bgp_aggregator = None,
+            Some(BgpAttributeType::Community) => self.bgp_community = None,
+            Some(BgpAttributeType::OriginatorId) => self.bgp_originator_id = None,
+            Some(BgpAttributeType::ClusterList) => self.bgp_cluster_list = None,
+            Some(BgpAttributeType::MpReachNlri) => self.bgp_mp_reach_nlri = None,
+            Some(BgpAttributeType::MpUnreachNlri) => self.bgp_mp_unreach_nlri = None,
+            Some(BgpAttributeType::ExtendedCommunity) => self.bgp_extended_community = None,
+            Some(BgpAttributeType::As4Path) => self.bgp_as4_path = None,
+            Some(BgpAttributeType::As4Aggregator) => self.bgp_as4_aggregator = None,
+            Some(BgpAttributeType::Ipv6ExtendedCommunity) => {
+                self.bgp_ipv6_extended_community = None
+            }
+            Some(BgpAttributeType::LargeCommunity) => self.bgp_large_community = None,
+            Some(BgpAttributeType::Otc) => self.bgp_otc = None,
+            _ => self
+                .bgp_unknown_attributes
+                .retain(|attribute| attribute.type_code != type_code),
+        }
+    }
+
+    /// Decode the value of a single attribute into its field
+    fn read_attribute<R: Read>(
+        &mut self,
+        reader: &mut R,
+        header: &BgpAttributeHeader,
+        as4: bool,
+    ) -> Result<(), Error> {
+        match header.attribute_type {
+            Some(BgpAttributeType::Origin) => {
+                self.bgp_origin = Some(BgpOrigin::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::AsPath) => {
+                self.bgp_as_path = Some(BgpAsPath::from_reader(
+                    reader,
+                    header.attribute_length,
+                    as4,
+                )?);
+            }
+            Some(BgpAttributeType::NextHop) => {
+                self.bgp_next_hop = Some(BgpNextHop::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::Community) => {
+                self.bgp_community =
+                    Some(BgpCommunity::from_reader(reader, header.attribute_length)?);
+            }
+            Some(BgpAttributeType::LargeCommunity) => {
+                self.bgp_large_community = Some(BgpLargeCommunity::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            Some(BgpAttributeType::ExtendedCommunity) => {
+                self.bgp_extended_community = Some(BgpExtendedCommunity::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            Some(BgpAttributeType::Ipv6ExtendedCommunity) => {
+                self.bgp_ipv6_extended_community = Some(BgpIpv6ExtendedCommunity::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            Some(BgpAttributeType::MultiExitDisc) => {
+                self.bgp_multi_exit_disc = Some(BgpMultiExitDisc::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::LocalPref) => {
+                self.bgp_local_pref = Some(BgpLocalPref::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::OriginatorId) => {
+                self.bgp_originator_id = Some(BgpOriginatorId::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::ClusterList) => {
+                self.bgp_cluster_list = Some(BgpClusterList::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            Some(BgpAttributeType::Aggregator) => {
+                self.bgp_aggregator =
+                    Some(BgpAggregator::from_reader(reader, header.attribute_length)?);
+            }
+            Some(BgpAttributeType::AtomicAggregate) => {
+                self.bgp_atomic_aggregate = true;
+            }
+            Some(BgpAttributeType::Otc) => {
+                self.bgp_otc = Some(BgpOtc::from_reader(reader)?);
+            }
+            Some(BgpAttributeType::As4Path) => {
+                self.bgp_as4_path = Some(BgpAsPath::from_reader(
+                    reader,
+                    header.attribute_length,
+                    true,
+                )?);
+            }
+            Some(BgpAttributeType::As4Aggregator) => {
+                self.bgp_as4_aggregator =
+                    Some(BgpAggregator::from_reader(reader, header.attribute_length)?);
+            }
+            Some(BgpAttributeType::MpReachNlri) => {
+                self.bgp_mp_reach_nlri = Some(BgpMpReachNlri::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            Some(BgpAttributeType::MpUnreachNlri) => {
+                self.bgp_mp_unreach_nlri = Some(BgpMpUnreachNlri::from_reader(
+                    reader,
+                    header.attribute_length,
+                )?);
+            }
+            _ => {
+                // keep unknown and unimplemented attributes as is
+                self.bgp_unknown_attributes
+                    .push(BgpUnknownAttribute::from_reader(reader, header)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuild AS_PATH and AGGREGATOR from AS4_PATH and AS4_AGGREGATOR
+    /// following RFC 6793 section 4.2.3
+    fn merge_as4_attributes(&mut self) {
+        if let Some(as4_aggregator) = &self.bgp_as4_aggregator {
+            match &self.bgp_aggregator {
+                // Aggregated by a 2-byte speaker, AS4_PATH is stale
+                Some(aggregator) if aggregator.asn != AS_TRANS => return,
+                _ => {
+                    self.bgp_aggregator = Some(BgpAggregator {
+                        asn: as4_aggregator.asn,
+                        ip: as4_aggregator.ip,
+                    })
+                }
+            }
+        }
+        if let (Some(as_path), Some(as4_path)) = (&self.bgp_as_path, &self.bgp_as4_path) {
+            self.bgp_as_path = Some(as_path.merge_as4_path(as4_path));
+        }
+    }
+
+    pub fn as_path_string(&self) -> String {
+        self.bgp_as_path
+            .as_ref()
+            .map(|as_path| as_path.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn cluster_list_string(&self) -> String {
+        self.bgp_cluster_list
+            .as_ref()
+            .map(|cluster_list| cluster_list.to_string())
+            .unwrap_or_default()
+    }
+
+    /// bgpdump flag of ATOMIC_AGGREGATE: AG when set, NAG otherwise
+    pub fn atomic_aggregate_string(&self) -> String {
+        if self.bgp_atomic_aggregate {
+            "AG".to_string()
+        } else {
+            "NAG".to_string()
+        }
+    }
+
+    pub fn origin_string(&self) -> String {
+        self.bgp_origin
+            .as_ref()
+            .map(|origin| origin.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// bgpdump-like multiline rendering, one attribute per line
+impl fmt::Display for BgpAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(origin) = &self.bgp_origin {
+            writeln!(f, "ORIGIN: {}", origin)?;
+        }
+        if let Some(as_path) = &self.bgp_as_path {
+            writeln!(f, "ASPATH: {}", as_path)?;
+        }
+        if let Some(next_hop) = &self.bgp_next_hop {
+            writeln!(f, "NEXT_HOP: {}", next_hop.0)?;
+        }
+        if let Some(multi_exit_disc) = &self.bgp_multi_exit_disc {
+            writeln!(f, "MULTI_EXIT_DISC: {}", multi_exit_disc.0)?;
+        }
+        if let Some(local_pref) = &self.bgp_local_pref {
+            writeln!(f, "LOCAL_PREF: {}", local_pref.0)?;
+        }
+        if let Some(originator_id) = &self.bgp_originator_id {
+            writeln!(f, "ORIGINATOR_ID: {}", originator_id.0)?;
+        }
+        if let Some(cluster_list) = &self.bgp_cluster_list {
+            writeln!(f, "CLUSTER_LIST: {}", cluster_list)?;
+        }
+        if let Some(communities) = &self.bgp_community {
+            writeln!(
+                f,
+                "COMMUNITIES: {}",
+                communities
+                    .0
+                    .iter()
+                    .map(|(asn, local)| format!("{}:{}", asn, local))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )?;
+        }
+        if let Some(communities) = &self.bgp_large_community {
+            writeln!(
+                f,
+                "LARGE_COMMUNITY: {}",
+                communities
+                    .0
+                    .iter()
+                    .map(|(asn, local, global)| format!("{}:{}:{}", asn, local, global))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )?;
+        }
+        if let Some(communities) = &self.bgp_extended_community {
+            writeln!(
+                f,
+                "EXTENDED_COMMUNITY: {}",
+                join_communities(&communities.0)
+            )?;
+        }
+        if let Some(communities) = &self.bgp_ipv6_extended_community {
+            writeln!(
+                f,
+                "IPV6_EXTENDED_COMMUNITY: {}",
+                join_communities(&communities.0)
+            )?;
+        }
+        if self.bgp_atomic_aggregate {
+            writeln!(f, "ATOMIC_AGGREGATE")?;
+        }
+        if let Some(aggregator) = &self.bgp_aggregator {
+            writeln!(f, "AGGREGATOR: {} {}", aggregator.asn, aggregator.ip)?;
+        }
+        if let Some(otc) = &self.bgp_otc {
+            writeln!(f, "OTC: {}", otc.0)?;
+        }
+        if let Some(mp_reach) = &self.bgp_mp_reach_nlri {
+            if let Some(next_hop) = mp_reach.next_hop {
+                writeln!(f, "NEXT_HOP: {}", next_hop)?;
+            }
+            if let Some(next_hop) = mp_reach.next_hop_link_local {
+                writeln!(f, "NEXT_HOP: {}", next_hop)?;
+            }
+            if let (Some(afi), Some(safi), false) =
+                (mp_reach.afi, mp_reach.safi, mp_reach.nlri.is_empty())
+            {
+                writeln!(f, "MP_REACH_NLRI({})", afi_safi_name(afi, safi))?;
+                for nlri in &mp_reach.nlri {
+                    writeln!(f, "  {}", nlri)?;
+                }
+            }
+        }
+        if let Some(mp_unreach) = &self.bgp_mp_unreach_nlri
+            && !mp_unreach.withdrawn_routes.is_empty()
+        {
+            writeln!(
+                f,
+                "MP_UNREACH_NLRI({})",
+                afi_safi_name(mp_unreach.afi, mp_unreach.safi)
+            )?;
+            for nlri in &mp_unreach.withdrawn_routes {
+                writeln!(f, "  {}", nlri)?;
+            }
+        }
+        for attribute in &self.bgp_unknown_attributes {
+            writeln!(
+                f,
+                "UNKNOWN_ATTR(type {}, flags 0x{:02x}, length {}): {}",
+                attribute.type_code,
+                attribute.flags,
+                attribute.data.len(),
+                hex::encode(&attribute.data)
+            )?;
+        }
+        for error in &self.attribute_errors {
+            writeln!(
+                f,
+                "ATTRIBUTE_ERROR: {}({}) {} {}",
+                attribute_name(error.type_code),
+                error.type_code,
+                error.action,
+                error.message
+            )?;
+        }
+        if let Some(action) = &self.error_action {
+            writeln!(f, "ERROR_ACTION: {}", action)?;
+        }
+        // Verbose mode, `{:#}`
+        if f.alternate() {
+            for (code, flags) in &self.attribute_flags {
+                writeln!(f, "FLAGS: {}({}) {}", attribute_name(*code), code, flags)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name of an attribute type code, e.g. AsPath or Unknown
+fn attribute_name(code: u8) -> String {
+    BgpAttributeType::from_repr(code)
+        .map(|attribute_type| attribute_type.to_string())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Name of an AFI/SAFI pair, e.g. IPV6_UNICAST or AFI25_SAFI65
+pub fn afi_safi_name(afi: u16, safi: u8) -> String {
+    let afi = Afi::from_repr(afi)
+        .map(|afi| afi.to_string())
+        .unwrap_or_else(|| format!("AFI{}", afi));
+    let safi = Safi::from_repr(safi)
+        .map(|safi| safi.to_string())
+        .unwrap_or_else(|| format!("SAFI{}", safi));
+    format!("{}_{}", afi, safi)
+}
+
+/// Read the NLRI entries packed until the end of the reader
+fn read_nlri_list<R: Read>(reader: &mut R, afi: u16, safi: u8) -> Result<Vec<Nlri>, Error> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let length = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let mut nlri = Vec::new();
+    while cursor.position() < length {
+        nlri.push(Nlri::from_reader(&mut cursor, afi, safi)?);
+    }
+    Ok(nlri)
+}
+
+impl BgpMpReachNlri {
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        let mut data = vec![0u8; length.into()];
+        reader.read_exact(&mut data)?;
+        // The abbreviated form only holds the next hop and its length
+        if data
+            .first()
+            .is_some_and(|&len| len as usize + 1 == data.len())
+        {
+            let (next_hop, next_hop_link_local) = read_next_hop(&data[1..])?;
+            return Ok(BgpMpReachNlri {
+                afi: None,
+                safi: None,
+                next_hop,
+                next_hop_link_local,
+                nlri: Vec::new(),
+            });
+        }
+        let mut cursor = Cursor::new(data);
+        let afi = cursor.read_u16::<BigEndian>()?;
+        let safi = cursor.read_u8()?;
+        let next_hop_length = cursor.read_u8()?;
+        let mut next_hop = vec![0u8; next_hop_length.into()];
+        cursor.read_exact(&mut next_hop)?;
+        let (next_hop, next_hop_link_local) = read_next_hop(&next_hop)?;
+        // Reserved
+        cursor.read_u8()?;
+        let nlri = read_nlri_list(&mut cursor, afi, safi)?;
+        Ok(BgpMpReachNlri {
+            afi: Some(afi),
+            safi: Some(safi),
+            next_hop,
+            next_hop_link_local,
+            nlri,
+        })
+    }
+}
+
+/// Decode a next hop field, route distinguishers of VPN next hops are dropped
+fn read_next_hop(data: &[u8]) -> Result<(Option<IpAddr>, Option<Ipv6Addr>), Error> {
+    let ipv6 = |data: &[u8]| Ipv6Addr::from(<[u8; 16]>::try_from(data).unwrap());
+    match data.len() {
+        0 => Ok((None, None)),
+        4 => Ok((
+            Some(IpAddr::V4(Ipv4Addr::new(
+                data[0], data[1], data[2], data[3],
+            ))),
+            None,
+        )),
+        12 => Ok((
+            Some(IpAddr::V4(Ipv4Addr::new(
+                data[8], data[9], data[10], data[11],
+            ))),
+            None,
+        )),
+        16 => Ok((Some(IpAddr::V6(ipv6(data))), None)),
+        24 => Ok((Some(IpAddr::V6(ipv6(&data[8..24]))), None)),
+        32 => Ok((
+            Some(IpAddr::V6(ipv6(&data[..16]))),
+            Some(ipv6(&data[16..32])),
+        )),
+        48 => Ok((
+            Some(IpAddr::V6(ipv6(&data[8..24]))),
+            Some(ipv6(&data[32..48])),
+        )),
+        length => Err(Error::InvalidNextHopLength(length as u8)),
+    }
+}
+
+impl BgpMpUnreachNlri {
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        let mut data = vec![0u8; length.into()];
+        reader.read_exact(&mut data)?;
+        let mut cursor = Cursor::new(data);
+        let afi = cursor.read_u16::<BigEndian>()?;
+        let safi = cursor.read_u8()?;
+        let withdrawn_routes = read_nlri_list(&mut cursor, afi, safi)?;
+        Ok(BgpMpUnreachNlri {
+            afi,
+            safi,
+            withdrawn_routes,
+        })
+    }
+}
+
 impl BgpOrigin {
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let origin = BgpOriginType::from_repr(reader.read_u8()?).ok_or(Error::BadMrtHeader)?;
+        let origin = reader.read_u8()?;
+        let origin = BgpOriginType::from_repr(origin).ok_or(Error::BadAttributeValue(
+            BgpAttributeType::Origin as u8,
+            origin,
+        ))?;
         Ok(BgpOrigin(origin))
     }
 }
@@ -117,17 +837,139 @@ impl fmt::Display for BgpOrigin {
 }
 
 impl BgpAsPath {
-    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let segment_type = reader.read_u8()?;
-        let segment_count: usize = reader.read_u8()?.into();
-        let mut segments = Vec::with_capacity(segment_count);
-        for _ in 0..segment_count {
-            segments.push(reader.read_i32::<BigEndian>()?);
-        }
-        Ok(BgpAsPath {
-            segment_type,
-            segments,
-        })
+    /// Read the `length` bytes of segments of an AS_PATH, `as4` selects
+    /// 4-byte instead of 2-byte ASNs
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16, as4: bool) -> Result<Self, Error> {
+        // Read the whole attribute first so that a bad segment does not
+        // desync the following attributes
+        let mut data = vec![0u8; length.into()];
+        reader.read_exact(&mut data)?;
+        let mut cursor = Cursor::new(data);
+        let mut segments = Vec::new();
+        while cursor.position() < length.into() {
+            let segment_type = cursor.read_u8()?;
+            let segment_type = AsPathSegmentType::from_repr(segment_type)
+                .ok_or(Error::BadAsPathSegmentType(segment_type))?;
+            let asn_count: usize = cursor.read_u8()?.into();
+            let mut asns = Vec::with_capacity(asn_count);
+            for _ in 0..asn_count {
+                asns.push(if as4 {
+                    cursor.read_u32::<BigEndian>()?
+                } else {
+                    cursor.read_u16::<BigEndian>()?.into()
+                });
+            }
+            segments.push(AsPathSegment { segment_type, asns });
+        }
+        Ok(BgpAsPath { segments })
+    }
+
+    /// Path length as used by the BGP decision process (RFC 4271 section
+    /// 9.1.2.2): an AS_SET counts as one AS, confederation segments don't count
+    pub fn path_length(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match segment.segment_type {
+                AsPathSegmentType::Sequence => segment.asns.len(),
+                AsPathSegmentType::Set => 1,
+                AsPathSegmentType::ConfedSequence | AsPathSegmentType::ConfedSet => 0,
+            })
+            .sum()
+    }
+
+    /// AS that originated the route, the last AS of the path. None when the
+    /// path ends with an AS_SET since the origin is then ambiguous
+    pub fn origin_asn(&self) -> Option<u32> {
+        let segment = self.segments.last()?;
+        match segment.segment_type {
+            AsPathSegmentType::Sequence | AsPathSegmentType::ConfedSequence => {
+                segment.asns.last().copied()
+            }
+            AsPathSegmentType::Set | AsPathSegmentType::ConfedSet => {
+                match segment.asns.as_slice() {
+                    [asn] => Some(*asn),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Merge the AS4_PATH received on a 2-byte session: the leading ASes of
+    /// AS_PATH missing from AS4_PATH are prepended to it. Returns the path
+    /// unchanged when AS4_PATH is longer than AS_PATH (RFC 6793 section 4.2.3)
+    pub fn merge_as4_path(&self, as4_path: &BgpAsPath) -> BgpAsPath {
+        let Some(mut remaining) = self.path_length().checked_sub(as4_path.path_length()) else {
+            return self.clone();
+        };
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            if remaining == 0 {
+                break;
+            }
+            match segment.segment_type {
+                AsPathSegmentType::Sequence => {
+                    let count = remaining.min(segment.asns.len());
+                    remaining -= count;
+                    segments.push(AsPathSegment {
+                        segment_type: segment.segment_type,
+                        asns: segment.asns[..count].to_vec(),
+                    });
+                }
+                AsPathSegmentType::Set => {
+                    remaining -= 1;
+                    segments.push(segment.clone());
+                }
+                AsPathSegmentType::ConfedSequence | AsPathSegmentType::ConfedSet => {
+                    segments.push(segment.clone());
+                }
+            }
+        }
+        // Confederation segments of AS4_PATH are discarded
+        for segment in &as4_path.segments {
+            match (segment.segment_type, segments.last_mut()) {
+                (AsPathSegmentType::ConfedSequence | AsPathSegmentType::ConfedSet, _) => {}
+                (AsPathSegmentType::Sequence, Some(last))
+                    if last.segment_type == AsPathSegmentType::Sequence =>
+                {
+                    last.asns.extend_from_slice(&segment.asns);
+                }
+                _ => segments.push(segment.clone()),
+            }
+        }
+        BgpAsPath { segments }
+    }
+}
+
+impl fmt::Display for AsPathSegment {
+    /// bgpdump notation: `a b` for sequences, `{a,b}` for sets, `(a b)` for
+    /// confederation sequences and `[a,b]` for confederation sets
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |separator: &str| {
+            self.asns
+                .iter()
+                .map(|asn| asn.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self.segment_type {
+            AsPathSegmentType::Sequence => write!(f, "{}", join(" ")),
+            AsPathSegmentType::Set => write!(f, "{{{}}}", join(",")),
+            AsPathSegmentType::ConfedSequence => write!(f, "({})", join(" ")),
+            AsPathSegmentType::ConfedSet => write!(f, "[{}]", join(",")),
+        }
+    }
+}
+
+impl fmt::Display for BgpAsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = self
+            .segments
+            .iter()
+            .filter(|segment| !segment.asns.is_empty())
+            .map(|segment| segment.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}", segments)
     }
 }
 
@@ -140,9 +982,75 @@ impl BgpNextHop {
     }
 }
 
-impl BgpAggregator {
+impl BgpLocalPref {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(BgpLocalPref(reader.read_u32::<BigEndian>()?))
+    }
+}
+
+impl BgpUnknownAttribute {
+    pub fn from_reader<R: Read>(
+        reader: &mut R,
+        header: &BgpAttributeHeader,
+    ) -> Result<Self, Error> {
+        let mut data = vec![0u8; header.attribute_length.into()];
+        reader.read_exact(&mut data)?;
+        Ok(BgpUnknownAttribute {
+            flags: header.attribute_flag,
+            type_code: header.attribute_code,
+            data,
+        })
+    }
+}
+
+impl BgpOtc {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(BgpOtc(reader.read_u32::<BigEndian>()?))
+    }
+}
+
+impl BgpOriginatorId {
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let asn = reader.read_u32::<BigEndian>()?;
+        Ok(BgpOriginatorId(Ipv4Addr::from_bits(
+            reader.read_u32::<BigEndian>()?,
+        )))
+    }
+}
+
+impl BgpClusterList {
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        if !length.is_multiple_of(4) {
+            return Err(Error::InvalidClusterListLength(length));
+        }
+        let mut cluster_list = Vec::with_capacity((length / 4).into());
+        for _ in 0..length / 4 {
+            cluster_list.push(Ipv4Addr::from_bits(reader.read_u32::<BigEndian>()?));
+        }
+        Ok(BgpClusterList(cluster_list))
+    }
+}
+
+impl fmt::Display for BgpClusterList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cluster_list = self
+            .0
+            .iter()
+            .map(|cluster_id| cluster_id.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}", cluster_list)
+    }
+}
+
+impl BgpAggregator {
+    /// Read an AGGREGATOR of `length` bytes, 6 with a 2-byte ASN or 8 with a
+    /// 4-byte ASN
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        let asn = match length {
+            6 => reader.read_u16::<BigEndian>()?.into(),
+            8 => reader.read_u32::<BigEndian>()?,
+            _ => return Err(Error::InvalidAggregatorLength(length)),
+        };
         let mut ip_bytes = [0u8; 4];
         reader.read_exact(&mut ip_bytes)?;
         let ip = Ipv4Addr::from(ip_bytes);
@@ -152,7 +1060,7 @@ impl BgpAggregator {
 
 impl BgpCommunity {
     pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
-        if length % 4 != 0 {
+        if !length.is_multiple_of(4) {
             return Err(Error::InvalidCommunityLength(length));
         }
         let community_count: usize = (length / 4).into();
@@ -168,7 +1076,7 @@ impl BgpCommunity {
 
 impl BgpLargeCommunity {
     pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
-        if length % 12 != 0 {
+        if !length.is_multiple_of(12) {
             return Err(Error::InvalidLargeCommunityLength(length));
         }
         let community_count: usize = (length / 12).into();
@@ -183,6 +1091,44 @@ impl BgpLargeCommunity {
     }
 }
 
+impl BgpExtendedCommunity {
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        if !length.is_multiple_of(8) {
+            return Err(Error::InvalidExtendedCommunityLength(length));
+        }
+        let mut community = Vec::with_capacity((length / 8).into());
+        for _ in 0..length / 8 {
+            let mut data = [0u8; 8];
+            reader.read_exact(&mut data)?;
+            community.push(ExtendedCommunity::from_bytes(&data));
+        }
+        Ok(BgpExtendedCommunity(community))
+    }
+}
+
+impl BgpIpv6ExtendedCommunity {
+    pub fn from_reader<R: Read>(reader: &mut R, length: u16) -> Result<Self, Error> {
+        if !length.is_multiple_of(20) {
+            return Err(Error::InvalidExtendedCommunityLength(length));
+        }
+        let mut community = Vec::with_capacity((length / 20).into());
+        for _ in 0..length / 20 {
+            let mut data = [0u8; 20];
+            reader.read_exact(&mut data)?;
+            community.push(ExtendedCommunity::from_ipv6_bytes(&data));
+        }
+        Ok(BgpIpv6ExtendedCommunity(community))
+    }
+}
+
+fn join_communities(communities: &[ExtendedCommunity]) -> String {
+    communities
+        .iter()
+        .map(|community| community.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 impl BgpMultiExitDisc {
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let metric = reader.read_u32::<BigEndian>()?;
@@ -204,7 +1150,7 @@ mod tests {
         assert!(header.is_ok());
         let header = header.unwrap();
         assert_eq!(header.attribute_flag, 0x10);
-        assert_eq!(header.attribute_type, BgpAttributeType::Origin);
+        assert_eq!(header.attribute_type, Some(BgpAttributeType::Origin));
         assert_eq!(header.attribute_length, 4);
         assert_eq!(header.offset, 8);
     }
@@ -218,34 +1164,242 @@ mod tests {
         assert!(header.is_ok());
         let header = header.unwrap();
         assert_eq!(header.attribute_flag, 0x00);
-        assert_eq!(header.attribute_type, BgpAttributeType::Origin);
+        assert_eq!(header.attribute_type, Some(BgpAttributeType::Origin));
         assert_eq!(header.attribute_length, 4);
         assert_eq!(header.offset, 7);
     }
 
     #[test]
-    fn test_reading_bgp_wrong_attribute_header() {
+    fn test_reading_bgp_attribute_header_max_length() {
         let mut cursor = Cursor::new(vec![
-            0x10, 0x0a, 0x00, 0x04, // BGP Attribute Header
+            0x10, 0x01, 0xff, 0xff, // extended length 0xffff
         ]);
-        let header = BgpAttributeHeader::from_reader(&mut cursor);
-        assert!(header.is_err());
+        let header = BgpAttributeHeader::from_reader(&mut cursor).unwrap();
+        assert_eq!(header.offset, 0x10003);
+        // a list cut inside such an attribute is an error, not an overflow
+        let mut cursor = Cursor::new(vec![0x10, 0x01, 0xff, 0xfc, 0x00]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 5, true).unwrap();
+        assert_eq!(attributes.attribute_errors.len(), 1);
+    }
+
+    #[test]
+    fn test_reading_bgp_unknown_attribute_header() {
+        let mut cursor = Cursor::new(vec![
+            0x10, 0x63, 0x00, 0x04, // BGP Attribute Header
+        ]);
+        let header = BgpAttributeHeader::from_reader(&mut cursor).unwrap();
+        assert_eq!(header.attribute_code, 0x63);
+        assert_eq!(header.attribute_type, None);
+    }
+
+    #[test]
+    fn test_reading_bgp_unknown_attributes() {
+        let mut cursor = Cursor::new(vec![
+            0xc0, 0x63, 0x02, 0xab, 0xcd, // unknown type 99
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0x80, 0x26, 0x00, // BFD discriminator, not decoded
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 12, true).unwrap();
+        assert_eq!(attributes.origin_string(), "IGP");
+        assert_eq!(attributes.bgp_unknown_attributes.len(), 2);
+        assert_eq!(attributes.bgp_unknown_attributes[0].data, vec![0xab, 0xcd]);
+        assert_eq!(
+            attributes.to_string(),
+            "ORIGIN: IGP\nUNKNOWN_ATTR(type 99, flags 0xc0, length 2): abcd\n\
+             UNKNOWN_ATTR(type 38, flags 0x80, length 0): \n"
+        );
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["bgp_unknown_attributes"][0]["type_code"], 99);
+        assert_eq!(json["bgp_unknown_attributes"][0]["flags"], 0xc0);
+        assert_eq!(json["bgp_unknown_attributes"][0]["data"], "abcd");
+    }
+
+    #[test]
+    fn test_attribute_flags() {
+        let flags = BgpAttributeFlags::from_bits(0xd0);
+        assert!(flags.optional && flags.transitive && flags.extended_length);
+        assert!(!flags.partial);
+        assert_eq!(flags.to_string(), "OPTIONAL TRANSITIVE EXTENDED_LENGTH");
+        assert_eq!(BgpAttributeFlags::from_bits(0x00).to_string(), "WELL_KNOWN");
+
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0xe0, 0x20, 0x0c, // large community, partial
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03,
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 19, true).unwrap();
+        assert_eq!(attributes.attribute_flags.len(), 2);
+        assert_eq!(attributes.attribute_flags[1].0, 32);
+        assert!(attributes.attribute_flags[1].1.partial);
+        assert_eq!(
+            format!("{:#}", attributes),
+            "ORIGIN: IGP\nLARGE_COMMUNITY: 1:2:3\n\
+             FLAGS: Origin(1) TRANSITIVE\nFLAGS: LargeCommunity(32) OPTIONAL TRANSITIVE PARTIAL\n"
+        );
+        assert!(!attributes.to_string().contains("FLAGS"));
+    }
+
+    #[test]
+    fn test_reading_malformed_attributes() {
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x01, 0x02, 0x00, 0x00, // origin with a trailing byte
+            0x40, 0x04, 0x02, 0x00, 0x01, // MED too short
+            0x40, 0x03, 0x04, 192, 0, 2, 1,    // next hop
+            0xff, // not part of the attributes
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 17, true).unwrap();
+        assert_eq!(cursor.position(), 17);
+        assert_eq!(attributes.origin_string(), "IGP");
+        assert!(attributes.bgp_multi_exit_disc.is_none());
+        assert_eq!(
+            attributes.bgp_next_hop.as_ref().unwrap().0,
+            Ipv4Addr::new(192, 0, 2, 1)
+        );
+        let errors = attributes
+            .attribute_errors
+            .iter()
+            .map(|error| error.type_code)
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![1, 4]);
+        assert!(attributes.to_string().contains(
+            "ATTRIBUTE_ERROR: Origin(1) TREAT_AS_WITHDRAW Bad length 2 for attribute 1\n"
+        ));
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["attribute_errors"][1]["type_code"], 4);
+        assert_eq!(json["attribute_errors"][1]["action"], "treat_as_withdraw");
+        assert!(json.get("error_action").is_none());
+    }
+
+    #[test]
+    fn test_rfc7606_error_actions() {
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0xc0, 0x07, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, // aggregator too short
+        ]);
+        let mut attributes = BgpAttributes::from_reader(&mut cursor, 12, true).unwrap();
+        assert_eq!(
+            attributes.most_severe_error(),
+            Some(ErrorAction::AttributeDiscard)
+        );
+        attributes.mark_error_action();
+        assert!(
+            attributes
+                .to_string()
+                .ends_with("ERROR_ACTION: ATTRIBUTE_DISCARD\n")
+        );
+        assert!(attributes.drop_malformed());
+        assert_eq!(attributes.origin_string(), "IGP");
+
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0xc0, 0x08, 0x03, 0x00, 0x00, 0x00, // community length not a multiple of 4
+            0x80, 0x0e, 0x02, 0x00, 0x02, // MP_REACH_NLRI truncated
+        ]);
+        let mut attributes = BgpAttributes::from_reader(&mut cursor, 15, true).unwrap();
+        assert_eq!(
+            attributes.attribute_errors[0].action,
+            ErrorAction::TreatAsWithdraw
+        );
+        assert_eq!(
+            attributes.most_severe_error(),
+            Some(ErrorAction::SessionReset)
+        );
+        assert!(!attributes.drop_malformed());
+    }
+
+    #[test]
+    fn test_bad_origin() {
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x01, 0x01, 0x03, // origin 3
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 4, true).unwrap();
+        assert!(attributes.bgp_origin.is_none());
+        let error = &attributes.attribute_errors[0];
+        assert_eq!(error.action, ErrorAction::TreatAsWithdraw);
+        assert_eq!(error.message, "Bad value 3 for attribute 1");
     }
 
     #[test]
     fn test_reading_bgp_aspath() {
         let mut cursor = Cursor::new(vec![
-            0x00, // segment_type
-            0x02, 0x00, 0x00, 0x00, // segment_count
-            0x01, 0x00, 0x00, 0x00, // segment_value
-            0x02, 0x00, 0x00, 0x00, // segment_value
+            0x02, // segment_type AS_SEQUENCE
+            0x02, // segment_count
+            0x00, 0x00, 0x00, 0x01, // segment_value
+            0x00, 0x00, 0x00, 0x02, // segment_value
         ]);
-        let as_path = BgpAsPath::from_reader(&mut cursor);
+        let as_path = BgpAsPath::from_reader(&mut cursor, 10, true);
         assert!(as_path.is_ok());
         let as_path = as_path.unwrap();
-        assert_eq!(as_path.segments.len(), 2);
-        assert_eq!(as_path.segments[0], 1);
-        assert_eq!(as_path.segments[1], 2);
+        assert_eq!(as_path.segments.len(), 1);
+        assert_eq!(
+            as_path.segments[0].segment_type,
+            AsPathSegmentType::Sequence
+        );
+        assert_eq!(as_path.segments[0].asns, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_reading_bgp_aspath_segments() {
+        let mut cursor = Cursor::new(vec![
+            0x03, 0x01, // AS_CONFED_SEQUENCE
+            0x00, 0x00, 0xfd, 0xe8, // 65000
+            0x02, 0x02, // AS_SEQUENCE
+            0x00, 0x00, 0x0d, 0x1c, // 3356
+            0xfa, 0x56, 0xea, 0x00, // 4200000000
+            0x01, 0x02, // AS_SET
+            0x00, 0x00, 0x00, 0x0a, // 10
+            0x00, 0x00, 0x00, 0x14, // 20
+        ]);
+        let as_path = BgpAsPath::from_reader(&mut cursor, 26, true).unwrap();
+        assert_eq!(as_path.segments.len(), 3);
+        assert_eq!(as_path.to_string(), "(65000) 3356 4200000000 {10,20}");
+        assert_eq!(as_path.path_length(), 3);
+        assert_eq!(as_path.origin_asn(), None);
+
+        let mut cursor = Cursor::new(vec![
+            0x02, 0x02, // AS_SEQUENCE
+            0x00, 0x00, 0x0d, 0x1c, // 3356
+            0xfa, 0x56, 0xea, 0x00, // 4200000000
+        ]);
+        let as_path = BgpAsPath::from_reader(&mut cursor, 10, true).unwrap();
+        assert_eq!(as_path.origin_asn(), Some(4200000000));
+        let json = serde_json::to_value(&as_path).unwrap();
+        assert_eq!(json["segments"][0]["segment_type"], "AS_SEQUENCE");
+
+        let mut cursor = Cursor::new(vec![0x05, 0x00]);
+        let as_path = BgpAsPath::from_reader(&mut cursor, 2, true);
+        assert!(matches!(
+            as_path.unwrap_err(),
+            Error::BadAsPathSegmentType(5)
+        ));
+    }
+
+    #[test]
+    fn test_merging_as4_path() {
+        let mut cursor = Cursor::new(vec![
+            0x02, 0x04, // AS_SEQUENCE
+            0xfd, 0xe8, // 65000
+            0x5b, 0xa0, // AS_TRANS
+            0x0d, 0x1c, // 3356
+            0x5b, 0xa0, // AS_TRANS
+            0x01, 0x02, // AS_SET
+            0x00, 0x0a, // 10
+            0x00, 0x14, // 20
+        ]);
+        let as_path = BgpAsPath::from_reader(&mut cursor, 16, false).unwrap();
+        assert_eq!(as_path.to_string(), "65000 23456 3356 23456 {10,20}");
+        let mut cursor = Cursor::new(vec![
+            0x02, 0x03, // AS_SEQUENCE
+            0x00, 0x03, 0x0d, 0x40, // 200000
+            0x00, 0x00, 0x0d, 0x1c, // 3356
+            0x00, 0x03, 0x0d, 0x41, // 200001
+        ]);
+        let as4_path = BgpAsPath::from_reader(&mut cursor, 14, true).unwrap();
+        let merged = as_path.merge_as4_path(&as4_path);
+        assert_eq!(merged.to_string(), "65000 23456 200000 3356 200001");
+        // AS4_PATH longer than AS_PATH is ignored
+        let merged = as4_path.merge_as4_path(&as_path);
+        assert_eq!(merged, as4_path);
     }
 
     #[test]
@@ -301,17 +1455,96 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_reading_bgp_extended_community() {
+        let mut cursor = Cursor::new(vec![
+            0x10, 0x10, 0x00, 0x10, // BGP Header type=16 (extended community) length=16
+            0x00, 0x02, 0xfd, 0xe8, 0x00, 0x00, 0x00, 0x64, // rt:65000:100
+            0x43, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // origin-validation:valid
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 20, true).unwrap();
+        let communities = attributes.bgp_extended_community.as_ref().unwrap();
+        assert_eq!(communities.0.len(), 2);
+        assert_eq!(
+            attributes.to_string(),
+            "EXTENDED_COMMUNITY: rt:65000:100 origin-validation:valid\n"
+        );
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["bgp_extended_community"][0]["type"], "route_target");
+        assert_eq!(json["bgp_extended_community"][1]["state"], "valid");
+
+        let mut cursor = Cursor::new(vec![0x00; 7]);
+        let community = BgpExtendedCommunity::from_reader(&mut cursor, 7);
+        assert!(matches!(
+            community.unwrap_err(),
+            Error::InvalidExtendedCommunityLength(7)
+        ));
+    }
+
+    #[test]
+    fn test_reading_bgp_route_reflection_attributes() {
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x05, 0x04, 0x00, 0x00, 0x00, 0x64, // LOCAL_PREF 100
+            0x80, 0x09, 0x04, 192, 0, 2, 1, // ORIGINATOR_ID 192.0.2.1
+            0x80, 0x0a, 0x08, 10, 0, 0, 1, 10, 0, 0, 2, // CLUSTER_LIST
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 25, true).unwrap();
+        assert_eq!(attributes.bgp_local_pref.as_ref().unwrap().0, 100);
+        assert_eq!(
+            attributes.to_string(),
+            "LOCAL_PREF: 100\nORIGINATOR_ID: 192.0.2.1\nCLUSTER_LIST: 10.0.0.1 10.0.0.2\n"
+        );
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["bgp_local_pref"], 100);
+        assert_eq!(json["bgp_originator_id"], "192.0.2.1");
+        assert_eq!(json["bgp_cluster_list"][1], "10.0.0.2");
+
+        let mut cursor = Cursor::new(vec![0x00; 6]);
+        let cluster_list = BgpClusterList::from_reader(&mut cursor, 6);
+        assert!(matches!(
+            cluster_list.unwrap_err(),
+            Error::InvalidClusterListLength(6)
+        ));
+    }
+
+    #[test]
+    fn test_reading_bgp_otc_and_atomic_aggregate() {
+        let mut cursor = Cursor::new(vec![
+            0x40, 0x06, 0x00, // ATOMIC_AGGREGATE
+            0xc0, 0x23, 0x04, 0x00, 0x00, 0xfd, 0xe8, // OTC 65000
+        ]);
+        let attributes = BgpAttributes::from_reader(&mut cursor, 10, true).unwrap();
+        assert!(attributes.bgp_atomic_aggregate);
+        assert_eq!(attributes.bgp_otc.as_ref().unwrap().0, 65000);
+        assert_eq!(attributes.atomic_aggregate_string(), "AG");
+        assert_eq!(attributes.to_string(), "ATOMIC_AGGREGATE\nOTC: 65000\n");
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(json["bgp_atomic_aggregate"], true);
+        assert_eq!(json["bgp_otc"], 65000);
+
+        let json = serde_json::to_value(BgpAttributes::default()).unwrap();
+        assert!(json.get("bgp_atomic_aggregate").is_none());
+    }
+
     #[test]
     fn test_reading_bgp_aggregator() {
         let mut cursor = Cursor::new(vec![
             0x00, 0x00, 0x00, 0x01, // asn
             0x01, 0x00, 0x00, 0x02, // IP
         ]);
-        let aggregator = BgpAggregator::from_reader(&mut cursor);
+        let aggregator = BgpAggregator::from_reader(&mut cursor, 8);
         assert!(aggregator.is_ok());
         let aggregator = aggregator.unwrap();
         assert_eq!(aggregator.asn, 1);
         assert_eq!(aggregator.ip, Ipv4Addr::new(1, 0, 0, 2));
+
+        let mut cursor = Cursor::new(vec![
+            0x5b, 0xa0, // asn AS_TRANS
+            0x01, 0x00, 0x00, 0x02, // IP
+        ]);
+        let aggregator = BgpAggregator::from_reader(&mut cursor, 6).unwrap();
+        assert_eq!(aggregator.asn, AS_TRANS);
+        assert_eq!(cursor.position(), 6);
     }
 
     #[test]
@@ -324,4 +1557,69 @@ mod tests {
         let multi_exit_disk = multi_exit_disk.unwrap();
         assert_eq!(multi_exit_disk.0, 1);
     }
+
+    #[test]
+    fn test_reading_bgp_mp_reach_nlri_abbreviated() {
+        let mut data = vec![0x20]; // next hop length
+        data.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        data.extend_from_slice(&"fe80::1".parse::<Ipv6Addr>().unwrap().octets());
+        let mut cursor = Cursor::new(data);
+        let mp_reach = BgpMpReachNlri::from_reader(&mut cursor, 33).unwrap();
+        assert_eq!(mp_reach.afi, None);
+        assert_eq!(
+            mp_reach.next_hop,
+            Some("2001:db8::1".parse::<IpAddr>().unwrap())
+        );
+        assert_eq!(
+            mp_reach.next_hop_link_local,
+            Some("fe80::1".parse::<Ipv6Addr>().unwrap())
+        );
+        assert!(mp_reach.nlri.is_empty());
+    }
+
+    #[test]
+    fn test_reading_bgp_mp_reach_nlri_full() {
+        let mut data = vec![
+            0x00, 0x02, // AFI IPv6
+            0x01, // SAFI unicast
+            0x10, // next hop length
+        ];
+        data.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        data.extend_from_slice(&[
+            0x00, // reserved
+            0x20, 0x20, 0x01, 0x0d, 0xb8, // 2001:db8::/32
+            0x30, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x01, // 2001:db8:1::/48
+        ]);
+        let length = data.len() as u16;
+        let mut cursor = Cursor::new(data);
+        let mp_reach = BgpMpReachNlri::from_reader(&mut cursor, length).unwrap();
+        assert_eq!(mp_reach.afi, Some(2));
+        assert_eq!(mp_reach.safi, Some(1));
+        assert_eq!(mp_reach.next_hop_link_local, None);
+        let nlri = mp_reach
+            .nlri
+            .iter()
+            .map(|nlri| nlri.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(nlri, vec!["2001:db8::/32", "2001:db8:1::/48"]);
+    }
+
+    #[test]
+    fn test_reading_bgp_mp_unreach_nlri() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x02, // AFI IPv6
+            0x01, // SAFI unicast
+            0x20, 0x20, 0x01, 0x0d, 0xb8, // 2001:db8::/32
+        ]);
+        let mp_unreach = BgpMpUnreachNlri::from_reader(&mut cursor, 8).unwrap();
+        assert_eq!(mp_unreach.withdrawn_routes.len(), 1);
+        assert_eq!(mp_unreach.withdrawn_routes[0].to_string(), "2001:db8::/32");
+        // bad next hop length
+        let mut cursor = Cursor::new(vec![0x03, 0x01, 0x02, 0x03]);
+        let mp_reach = BgpMpReachNlri::from_reader(&mut cursor, 4);
+        assert!(matches!(
+            mp_reach.unwrap_err(),
+            Error::InvalidNextHopLength(3)
+        ));
+    }
 }
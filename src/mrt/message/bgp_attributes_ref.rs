@@ -0,0 +1,162 @@
+use super::{BgpAttributeFlags, BgpAttributeHeader, BgpAttributeType, BgpAttributes};
+use crate::mrt::Error;
+use std::io;
+
+/// Path attributes borrowed from the input, only split or decoded on access
+#[derive(Debug, Clone, Copy)]
+pub struct BgpAttributesRef<'a> {
+    data: &'a [u8], // Attributes as found on the wire
+    as4: bool,      // AS_PATH holds 4-byte ASNs
+}
+
+/// One path attribute borrowed from the input
+#[derive(Debug, Clone, Copy)]
+pub struct BgpAttributeRef<'a> {
+    pub flags: BgpAttributeFlags,
+    pub type_code: u8,
+    pub value: &'a [u8], // Attribute value, header excluded
+    raw: &'a [u8],       // Whole attribute, header included
+    as4: bool,
+}
+
+/// Iterator over the attributes of a [`BgpAttributesRef`], stops after the
+/// first attribute running past the end of the data
+#[derive(Debug, Clone)]
+pub struct BgpAttributeRefIter<'a> {
+    data: &'a [u8],
+    as4: bool,
+}
+
+impl<'a> BgpAttributesRef<'a> {
+    pub fn new(data: &'a [u8], as4: bool) -> Self {
+        BgpAttributesRef { data, as4 }
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn iter(&self) -> BgpAttributeRefIter<'a> {
+        BgpAttributeRefIter {
+            data: self.data,
+            as4: self.as4,
+        }
+    }
+
+    /// First attribute with this type code, if any
+    pub fn get(&self, attribute_type: BgpAttributeType) -> Option<BgpAttributeRef<'a>> {
+        let type_code = attribute_type as u8;
+        self.iter()
+            .map_while(|attribute| attribute.ok())
+            .find(|attribute| attribute.type_code == type_code)
+    }
+
+    /// Decode all the attributes, like `BgpAttributes::from_reader`
+    pub fn decode(&self) -> Result<BgpAttributes, Error> {
+        let length = u16::try_from(self.data.len())
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        BgpAttributes::from_reader(&mut { self.data }, length, self.as4)
+    }
+}
+
+impl<'a> BgpAttributeRef<'a> {
+    /// None for the attributes we don't decode
+    pub fn attribute_type(&self) -> Option<BgpAttributeType> {
+        BgpAttributeType::from_repr(self.type_code)
+    }
+
+    /// Decode this attribute alone, the other fields are left unset
+    pub fn decode(&self) -> Result<BgpAttributes, Error> {
+        // The header and a value of 0xfffc bytes or more don't fit in the
+        // u16 attribute length of `BgpAttributes::from_reader`
+        let length = u16::try_from(self.raw.len())
+            .map_err(|_| Error::BadAttributeLength(self.type_code, self.value.len() as u16))?;
+        BgpAttributes::from_reader(&mut { self.raw }, length, self.as4)
+    }
+}
+
+impl<'a> Iterator for BgpAttributeRefIter<'a> {
+    type Item = Result<BgpAttributeRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let header = match BgpAttributeHeader::from_reader(&mut { self.data }) {
+            Ok(header) => header,
+            Err(e) => {
+                self.data = &[];
+                return Some(Err(e));
+            }
+        };
+        let flags = BgpAttributeFlags::from_bits(header.attribute_flag);
+        let header_length: usize = if flags.extended_length { 4 } else { 3 };
+        let split = header_length
+            .checked_add(header.attribute_length.into())
+            .and_then(|length| self.data.split_at_checked(length));
+        let Some((raw, rest)) = split else {
+            self.data = &[];
+            return Some(Err(Error::BadAttributeLength(
+                header.attribute_code,
+                header.attribute_length,
+            )));
+        };
+        self.data = rest;
+        Some(Ok(BgpAttributeRef {
+            flags,
+            type_code: header.attribute_code,
+            value: &raw[header_length..],
+            raw,
+            as4: self.as4,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_attributes() {
+        let data = [
+            0x40, 0x01, 0x01, 0x02, // origin INCOMPLETE
+            0x50, 0x02, 0x00, 0x06, // as path, extended length 6
+            0x02, 0x01, 0x00, 0x00, 0xfd, 0xe8, // AS_SEQUENCE 65000
+            0xc0, 0x08, 0x08, 0x00, 0x00, // community, cut short
+        ];
+        let attributes = BgpAttributesRef::new(&data, true);
+        let mut iter = attributes.iter();
+        let origin = iter.next().unwrap().unwrap();
+        assert_eq!(origin.attribute_type(), Some(BgpAttributeType::Origin));
+        assert_eq!(origin.value, &[0x02]);
+        let as_path = iter.next().unwrap().unwrap();
+        assert!(as_path.flags.extended_length);
+        // the value borrows from the input
+        assert!(std::ptr::eq(as_path.value.as_ptr(), &data[8]));
+        assert_eq!(as_path.decode().unwrap().as_path_string(), "65000");
+        assert!(matches!(
+            iter.next().unwrap(),
+            Err(Error::BadAttributeLength(8, 8))
+        ));
+        assert!(iter.next().is_none());
+
+        let origin = attributes.get(BgpAttributeType::Origin).unwrap();
+        assert_eq!(origin.decode().unwrap().origin_string(), "INCOMPLETE");
+        assert!(attributes.get(BgpAttributeType::NextHop).is_none());
+        let decoded = attributes.decode().unwrap();
+        assert_eq!(decoded.as_path_string(), "65000");
+        assert_eq!(decoded.attribute_errors.len(), 1);
+    }
+
+    #[test]
+    fn test_attribute_longer_than_the_data() {
+        // extended length 0xffff, only one byte of value
+        let data = [0x10, 0x01, 0xff, 0xff, 0x00];
+        let mut iter = BgpAttributesRef::new(&data, true).iter();
+        assert!(matches!(
+            iter.next().unwrap(),
+            Err(Error::BadAttributeLength(1, 0xffff))
+        ));
+        assert!(iter.next().is_none());
+    }
+}
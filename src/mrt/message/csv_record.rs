@@ -0,0 +1,26 @@
+use chrono::DateTime;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr};
+
+/// One bgpdump-like `-m` line, shared by every record type written as CSV
+#[derive(Debug, Serialize)]
+pub struct CsvRecord {
+    pub record_type: String,
+    pub datetime: DateTime<chrono::Utc>,
+    pub entry_type: String,
+    pub peer_ip: Option<IpAddr>, // None for a RIB_GENERIC with undecoded entries
+    pub peer_asn: Option<u32>,
+    pub prefix_with_len: String,
+    pub as_path: String,
+    pub bgp_origin: String,
+    pub safi: String,
+    pub path_id: Option<u32>,
+    pub local_pref: Option<u32>,
+    pub originator_id: Option<Ipv4Addr>,
+    pub cluster_list: String,
+    pub atomic_aggregate: String, // AG or NAG like bgpdump, empty for withdrawals
+    pub otc: Option<u32>,
+    pub error_action: String, // Only set when malformed routes are marked
+    pub peer_latitude: Option<f32>, // Only set from a GEO_PEER_TABLE
+    pub peer_longitude: Option<f32>,
+}
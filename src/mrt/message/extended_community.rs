@@ -0,0 +1,290 @@
+use serde::{Serialize, Serializer};
+use serde_with::{hex::Hex, serde_as};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use strum_macros::{Display, FromRepr};
+
+/// Global administrator of an address or AS specific extended community
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Administrator {
+    Asn(u32),
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+}
+
+/// RPKI origin validation state (RFC 8097)
+#[derive(Debug, FromRepr, Display, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "kebab-case")]
+#[repr(u8)]
+pub enum OriginValidationState {
+    Valid = 0,
+    NotFound = 1,
+    Invalid = 2,
+}
+
+/// A decoded extended community (RFC 4360) or IPv6 address specific extended
+/// community (RFC 5701), communities we don't decode are kept as raw bytes
+#[serde_as]
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtendedCommunity {
+    RouteTarget {
+        global: Administrator,
+        local: u32,
+    },
+    RouteOrigin {
+        global: Administrator,
+        local: u32,
+    },
+    /// OSPF domain identifier (RFC 4577)
+    OspfDomainId {
+        #[serde_as(as = "Hex")]
+        id: Vec<u8>,
+    },
+    /// OSPF route type (RFC 4577)
+    OspfRouteType {
+        area: Ipv4Addr,
+        route_type: u8,
+        options: u8,
+    },
+    /// OSPF router ID (RFC 4577)
+    OspfRouterId {
+        router_id: Ipv4Addr,
+    },
+    OriginValidation {
+        state: OriginValidationState,
+    },
+    /// Flowspec traffic-rate in bytes per second (RFC 8955)
+    FlowspecTrafficRate {
+        asn: u16,
+        rate: f32,
+    },
+    /// Flowspec traffic-action sample and terminal bits (RFC 8955)
+    FlowspecTrafficAction {
+        sample: bool,
+        terminal: bool,
+    },
+    /// Flowspec redirect to a VRF route target (RFC 8955)
+    FlowspecRedirect {
+        global: Administrator,
+        local: u32,
+    },
+    /// Flowspec traffic-marking DSCP value (RFC 8955)
+    FlowspecTrafficMarking {
+        dscp: u8,
+    },
+    Raw {
+        #[serde_as(as = "Hex")]
+        data: Vec<u8>,
+    },
+}
+
+impl ExtendedCommunity {
+    /// Decode one 8-byte extended community
+    pub fn from_bytes(data: &[u8; 8]) -> Self {
+        let value = &data[2..];
+        let asn2 = || Administrator::Asn(u16::from_be_bytes([value[0], value[1]]).into());
+        let asn4 =
+            || Administrator::Asn(u32::from_be_bytes([value[0], value[1], value[2], value[3]]));
+        let ipv4 = || Administrator::Ipv4(Ipv4Addr::new(value[0], value[1], value[2], value[3]));
+        let local2 = || u16::from_be_bytes([value[4], value[5]]).into();
+        let local4 = || u32::from_be_bytes([value[2], value[3], value[4], value[5]]);
+        // The high order type bit 0x40 only tells whether the community is transitive
+        match (data[0], data[1]) {
+            (0x00 | 0x40, 0x02) => ExtendedCommunity::RouteTarget {
+                global: asn2(),
+                local: local4(),
+            },
+            (0x01 | 0x41, 0x02) => ExtendedCommunity::RouteTarget {
+                global: ipv4(),
+                local: local2(),
+            },
+            (0x02 | 0x42, 0x02) => ExtendedCommunity::RouteTarget {
+                global: asn4(),
+                local: local2(),
+            },
+            (0x00 | 0x40, 0x03) => ExtendedCommunity::RouteOrigin {
+                global: asn2(),
+                local: local4(),
+            },
+            (0x01 | 0x41, 0x03) => ExtendedCommunity::RouteOrigin {
+                global: ipv4(),
+                local: local2(),
+            },
+            (0x02 | 0x42, 0x03) => ExtendedCommunity::RouteOrigin {
+                global: asn4(),
+                local: local2(),
+            },
+            (0x00..=0x02, 0x05) => ExtendedCommunity::OspfDomainId { id: value.to_vec() },
+            (0x03, 0x06) => ExtendedCommunity::OspfRouteType {
+                area: Ipv4Addr::new(value[0], value[1], value[2], value[3]),
+                route_type: value[4],
+                options: value[5],
+            },
+            (0x01, 0x07) => ExtendedCommunity::OspfRouterId {
+                router_id: Ipv4Addr::new(value[0], value[1], value[2], value[3]),
+            },
+            (0x43, 0x00) => match OriginValidationState::from_repr(value[5]) {
+                Some(state) => ExtendedCommunity::OriginValidation { state },
+                None => ExtendedCommunity::Raw {
+                    data: data.to_vec(),
+                },
+            },
+            (0x80, 0x06) => ExtendedCommunity::FlowspecTrafficRate {
+                asn: u16::from_be_bytes([value[0], value[1]]),
+                rate: f32::from_be_bytes([value[2], value[3], value[4], value[5]]),
+            },
+            (0x80, 0x07) => ExtendedCommunity::FlowspecTrafficAction {
+                sample: value[5] & 0x02 == 0x02,
+                terminal: value[5] & 0x01 == 0x01,
+            },
+            (0x80, 0x08) => ExtendedCommunity::FlowspecRedirect {
+                global: asn2(),
+                local: local4(),
+            },
+            (0x81, 0x08) => ExtendedCommunity::FlowspecRedirect {
+                global: ipv4(),
+                local: local2(),
+            },
+            (0x82, 0x08) => ExtendedCommunity::FlowspecRedirect {
+                global: asn4(),
+                local: local2(),
+            },
+            (0x80, 0x09) => ExtendedCommunity::FlowspecTrafficMarking {
+                dscp: value[5] & 0x3f,
+            },
+            _ => ExtendedCommunity::Raw {
+                data: data.to_vec(),
+            },
+        }
+    }
+
+    /// Decode one 20-byte IPv6 address specific extended community
+    pub fn from_ipv6_bytes(data: &[u8; 20]) -> Self {
+        let global =
+            Administrator::Ipv6(Ipv6Addr::from(<[u8; 16]>::try_from(&data[2..18]).unwrap()));
+        let local = u16::from_be_bytes([data[18], data[19]]).into();
+        match (data[0], data[1]) {
+            (0x00 | 0x40, 0x02) => ExtendedCommunity::RouteTarget { global, local },
+            (0x00 | 0x40, 0x03) => ExtendedCommunity::RouteOrigin { global, local },
+            (0x00, 0x0d) => ExtendedCommunity::FlowspecRedirect { global, local },
+            _ => ExtendedCommunity::Raw {
+                data: data.to_vec(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Administrator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Administrator::Asn(asn) => write!(f, "{}", asn),
+            Administrator::Ipv4(ip) => write!(f, "{}", ip),
+            Administrator::Ipv6(ip) => write!(f, "{}", ip),
+        }
+    }
+}
+
+impl Serialize for Administrator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl fmt::Display for ExtendedCommunity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtendedCommunity::RouteTarget { global, local } => {
+                write!(f, "rt:{}:{}", global, local)
+            }
+            ExtendedCommunity::RouteOrigin { global, local } => {
+                write!(f, "ro:{}:{}", global, local)
+            }
+            ExtendedCommunity::OspfDomainId { id } => {
+                write!(f, "ospf-domain-id:{}", hex::encode(id))
+            }
+            ExtendedCommunity::OspfRouteType {
+                area,
+                route_type,
+                options,
+            } => write!(f, "ospf-route-type:{}:{}:{}", area, route_type, options),
+            ExtendedCommunity::OspfRouterId { router_id } => {
+                write!(f, "ospf-router-id:{}", router_id)
+            }
+            ExtendedCommunity::OriginValidation { state } => {
+                write!(f, "origin-validation:{}", state)
+            }
+            ExtendedCommunity::FlowspecTrafficRate { asn, rate } => {
+                write!(f, "traffic-rate:{}:{}", asn, rate)
+            }
+            ExtendedCommunity::FlowspecTrafficAction { sample, terminal } => {
+                write!(
+                    f,
+                    "traffic-action:{}{}",
+                    if *sample { "S" } else { "" },
+                    if *terminal { "T" } else { "" }
+                )
+            }
+            ExtendedCommunity::FlowspecRedirect { global, local } => {
+                write!(f, "redirect:{}:{}", global, local)
+            }
+            ExtendedCommunity::FlowspecTrafficMarking { dscp } => {
+                write!(f, "traffic-marking:{}", dscp)
+            }
+            ExtendedCommunity::Raw { data } => write!(f, "0x{}", hex::encode(data)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoding_route_target() {
+        let community = ExtendedCommunity::from_bytes(&[0x00, 0x02, 0xfd, 0xe8, 0, 0, 0, 100]);
+        assert_eq!(
+            community,
+            ExtendedCommunity::RouteTarget {
+                global: Administrator::Asn(65000),
+                local: 100
+            }
+        );
+        assert_eq!(community.to_string(), "rt:65000:100");
+        let community = ExtendedCommunity::from_bytes(&[0x01, 0x03, 192, 0, 2, 1, 0, 7]);
+        assert_eq!(community.to_string(), "ro:192.0.2.1:7");
+        let community = ExtendedCommunity::from_bytes(&[0x02, 0x02, 0, 3, 0x0d, 0x40, 0, 1]);
+        assert_eq!(community.to_string(), "rt:200000:1");
+        let json = serde_json::to_value(&community).unwrap();
+        assert_eq!(json["type"], "route_target");
+        assert_eq!(json["global"], "200000");
+    }
+
+    #[test]
+    fn test_decoding_origin_validation_and_flowspec() {
+        let community = ExtendedCommunity::from_bytes(&[0x43, 0x00, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(community.to_string(), "origin-validation:invalid");
+        let json = serde_json::to_value(&community).unwrap();
+        assert_eq!(json["state"], "invalid");
+        // traffic-rate 0 is a discard action
+        let community = ExtendedCommunity::from_bytes(&[0x80, 0x06, 0xfd, 0xe8, 0, 0, 0, 0]);
+        assert_eq!(community.to_string(), "traffic-rate:65000:0");
+        let community = ExtendedCommunity::from_bytes(&[0x80, 0x07, 0, 0, 0, 0, 0, 0x03]);
+        assert_eq!(community.to_string(), "traffic-action:ST");
+        let community = ExtendedCommunity::from_bytes(&[0x80, 0x09, 0, 0, 0, 0, 0, 0x2e]);
+        assert_eq!(community.to_string(), "traffic-marking:46");
+    }
+
+    #[test]
+    fn test_decoding_unknown_and_ipv6() {
+        let community = ExtendedCommunity::from_bytes(&[0x06, 0x00, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(community.to_string(), "0x0600010203040506");
+        let mut data = [0u8; 20];
+        data[1] = 0x02;
+        data[2..18].copy_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        data[19] = 10;
+        let community = ExtendedCommunity::from_ipv6_bytes(&data);
+        assert_eq!(community.to_string(), "rt:2001:db8::1:10");
+    }
+}
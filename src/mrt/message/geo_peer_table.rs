@@ -0,0 +1,149 @@
+use super::peer_index_table::PeerEntry;
+use crate::mrt::Error;
+use byteorder::{BigEndian, ReadBytesExt};
+use serde::Serialize;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Latitude and longitude in degrees, NaN when the location is not disclosed
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+pub struct GeoLocation {
+    pub latitude: f32,
+    pub longitude: f32,
+}
+
+/// One peer of the GEO_PEER_TABLE, unlike in the PEER_INDEX_TABLE the entry
+/// has no AS number
+#[derive(Debug)]
+pub struct GeoPeerEntry {
+    pub bgp_id: u32,           // BGP ID of the peer
+    pub peer_ip: IpAddr,       // IP address of the peer
+    pub location: GeoLocation, // Location of the peer
+}
+
+/// GEO_PEER_TABLE record (RFC 6397), location of the collector and its peers
+#[derive(Debug)]
+pub struct GeoPeerTable {
+    pub collector_bgp_id: u32,           // BGP ID of the collector
+    pub view_name_len: u16,              // Length of the view name
+    pub view_name: String,               // Name of the view
+    pub collector_location: GeoLocation, // Location of the collector
+    pub nentries: u16,                   // Number of entries in the table
+    pub entries: Vec<GeoPeerEntry>,
+}
+
+impl GeoLocation {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let latitude = reader.read_f32::<BigEndian>()?;
+        let longitude = reader.read_f32::<BigEndian>()?;
+        Ok(GeoLocation {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+impl GeoPeerEntry {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        // Only the IPv6 bit of the peer type is meaningful here
+        let peer_type = reader.read_u8()?;
+        let bgp_id = reader.read_u32::<BigEndian>()?;
+        let peer_ip = if peer_type & 0x01 == 0x01 {
+            IpAddr::V6(Ipv6Addr::from_bits(reader.read_u128::<BigEndian>()?))
+        } else {
+            IpAddr::V4(Ipv4Addr::from_bits(reader.read_u32::<BigEndian>()?))
+        };
+        let location = GeoLocation::from_reader(reader)?;
+        Ok(GeoPeerEntry {
+            bgp_id,
+            peer_ip,
+            location,
+        })
+    }
+}
+
+impl GeoPeerTable {
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let collector_bgp_id = reader.read_u32::<BigEndian>()?;
+        let view_name_len = reader.read_u16::<BigEndian>()?;
+        let mut view_name = vec![0u8; view_name_len as usize];
+        reader.read_exact(&mut view_name)?;
+        let view_name = String::from_utf8(view_name)?;
+        let collector_location = GeoLocation::from_reader(reader)?;
+        let nentries = reader.read_u16::<BigEndian>()?;
+        // Read the peer entries
+        let mut entries = Vec::with_capacity(nentries.into());
+        for _ in 0..nentries {
+            entries.push(GeoPeerEntry::from_reader(reader)?);
+        }
+        Ok(GeoPeerTable {
+            collector_bgp_id,
+            view_name_len,
+            view_name,
+            collector_location,
+            nentries,
+            entries,
+        })
+    }
+
+    /// Location of the peer with this BGP ID and IP address, if any
+    pub fn peer_location(&self, peer: &PeerEntry) -> Option<GeoLocation> {
+        self.entries
+            .iter()
+            .find(|entry| entry.bgp_id == peer.bgp_id && entry.peer_ip == peer.peer_ip)
+            .map(|entry| entry.location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_geo_peer_table_from_reader() {
+        let mut data = vec![
+            0, 0, 0, 1, // collector_bgp_id
+            0, 4, // view_name_len
+            b't', b'e', b's', b't', // view_name
+        ];
+        data.extend_from_slice(&48.85f32.to_be_bytes()); // collector latitude
+        data.extend_from_slice(&2.35f32.to_be_bytes()); // collector longitude
+        data.extend_from_slice(&[
+            0, 0x02, // nentries
+            0x00, // peer_type (IPv4)
+            0x00, 0x00, 0x00, 0x02, // bgp_id
+            192, 0, 2, 1, // peer_ip
+        ]);
+        data.extend_from_slice(&(-33.87f32).to_be_bytes()); // peer latitude
+        data.extend_from_slice(&f32::NAN.to_be_bytes()); // peer longitude
+        data.extend_from_slice(&[
+            0x01, // peer_type (IPv6)
+            0x00, 0x00, 0x00, 0x03, // bgp_id
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, // peer_ip
+        ]);
+        data.extend_from_slice(&51.5f32.to_be_bytes()); // peer latitude
+        data.extend_from_slice(&(-0.12f32).to_be_bytes()); // peer longitude
+        let mut cursor = Cursor::new(data);
+        let table = GeoPeerTable::from_reader(&mut cursor).unwrap();
+        // the whole record is consumed
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+        assert_eq!(table.view_name, "test");
+        assert_eq!(table.collector_location.latitude, 48.85);
+        assert_eq!(
+            table.entries[0].peer_ip,
+            IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))
+        );
+        assert_eq!(table.entries[1].bgp_id, 3);
+        assert_eq!(table.entries[1].location.latitude, 51.5);
+        let peer = PeerEntry {
+            bgp_id: 2,
+            peer_ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+            peer_asn: 65000,
+            location: None,
+        };
+        let location = table.peer_location(&peer).unwrap();
+        assert_eq!(location.latitude, -33.87);
+        assert!(location.longitude.is_nan());
+    }
+}
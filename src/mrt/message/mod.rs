@@ -1,19 +1,89 @@
+pub mod afi;
+pub use afi::Afi;
+pub use afi::Safi;
+
+pub mod nlri;
+pub use nlri::Nlri;
+pub use nlri::Prefix;
+pub use nlri::RouteDistinguisher;
+
 pub mod peer_index_table;
+pub use peer_index_table::PeerEntry;
 pub use peer_index_table::PeerIndexTable;
 
-pub mod rib_ipv4_unicast;
-pub use rib_ipv4_unicast::RibIpV4Unicast;
+pub mod geo_peer_table;
+pub use geo_peer_table::GeoLocation;
+pub use geo_peer_table::GeoPeerEntry;
+pub use geo_peer_table::GeoPeerTable;
+
+pub mod rib_unicast;
+pub use rib_unicast::RibUnicast;
+
+pub mod rib_generic;
+pub use rib_generic::RibGeneric;
+
+pub mod rib_ref;
+pub use rib_ref::RibEntryRef;
+pub use rib_ref::RibEntryRefIter;
+pub use rib_ref::RibRef;
+
+pub mod csv_record;
+pub use csv_record::CsvRecord;
+
+pub mod write_records;
+pub use write_records::WriteRecords;
+
+pub mod table_dump;
+pub use table_dump::TableDump;
 
 pub mod rib_entry;
-pub use rib_entry::RibEntry;
+pub use rib_entry::{MalformedRoutes, RibEntry};
+
+pub mod bgp4mp_header;
+pub use bgp4mp_header::Bgp4MpHeader;
+
+pub mod bgp4mp_message;
+pub use bgp4mp_message::Bgp4MpMessage;
+pub use bgp4mp_message::BgpMessageType;
+pub use bgp4mp_message::BgpUpdate;
+
+pub mod bgp4mp_state_change;
+pub use bgp4mp_state_change::Bgp4MpStateChange;
+pub use bgp4mp_state_change::BgpState;
+
+pub mod extended_community;
+pub use extended_community::Administrator;
+pub use extended_community::ExtendedCommunity;
+pub use extended_community::OriginValidationState;
 
 pub mod bgp_attribute;
+pub use bgp_attribute::AsPathSegment;
+pub use bgp_attribute::AsPathSegmentType;
 pub use bgp_attribute::BgpAggregator;
 pub use bgp_attribute::BgpAsPath;
+pub use bgp_attribute::BgpAttributeError;
+pub use bgp_attribute::BgpAttributeFlags;
 pub use bgp_attribute::BgpAttributeHeader;
 pub use bgp_attribute::BgpAttributeType;
+pub use bgp_attribute::BgpAttributes;
+pub use bgp_attribute::BgpClusterList;
 pub use bgp_attribute::BgpCommunity;
+pub use bgp_attribute::BgpExtendedCommunity;
+pub use bgp_attribute::BgpIpv6ExtendedCommunity;
 pub use bgp_attribute::BgpLargeCommunity;
+pub use bgp_attribute::BgpLocalPref;
+pub use bgp_attribute::BgpMpReachNlri;
+pub use bgp_attribute::BgpMpUnreachNlri;
 pub use bgp_attribute::BgpMultiExitDisc;
 pub use bgp_attribute::BgpNextHop;
 pub use bgp_attribute::BgpOrigin;
+pub use bgp_attribute::BgpOriginType;
+pub use bgp_attribute::BgpOriginatorId;
+pub use bgp_attribute::BgpOtc;
+pub use bgp_attribute::BgpUnknownAttribute;
+pub use bgp_attribute::ErrorAction;
+
+pub mod bgp_attributes_ref;
+pub use bgp_attributes_ref::BgpAttributeRef;
+pub use bgp_attributes_ref::BgpAttributeRefIter;
+pub use bgp_attributes_ref::BgpAttributesRef;
@@ -0,0 +1,298 @@
+use super::{Afi, Safi};
+use crate::mrt::Error;
+use byteorder::{BigEndian, ReadBytesExt};
+use serde::{Serialize, Serializer};
+use serde_with::{hex::Hex, serde_as};
+use std::fmt;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An IP prefix as encoded in the NLRI: a length in bits followed by the
+/// minimum number of octets needed to hold it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prefix {
+    pub addr: IpAddr,
+    pub len: u8,
+}
+
+/// Route distinguisher of an MPLS VPN route (RFC 4364 section 4.2)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteDistinguisher(pub [u8; 8]);
+
+#[serde_as]
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Nlri {
+    Prefix {
+        prefix: Prefix,
+    },
+    Labeled {
+        labels: Vec<u32>,
+        prefix: Prefix,
+    },
+    Vpn {
+        labels: Vec<u32>,
+        rd: RouteDistinguisher,
+        prefix: Prefix,
+    },
+    /// NLRI of an AFI/SAFI we don't decode, everything up to the end of the
+    /// attribute or record
+    Raw {
+        #[serde_as(as = "Hex")]
+        data: Vec<u8>,
+    },
+}
+
+impl Prefix {
+    pub fn from_reader<R: Read>(reader: &mut R, afi: Afi) -> Result<Self, Error> {
+        let len = reader.read_u8()?;
+        Prefix::from_reader_with_len(reader, afi, len)
+    }
+
+    /// Read the address octets of a prefix whose length in bits is already known
+    pub fn from_reader_with_len<R: Read>(reader: &mut R, afi: Afi, len: u8) -> Result<Self, Error> {
+        // The Prefix field contains address prefixes followed by enough
+        // trailing bits to make the end of the field fall on an octet boundary
+        let len_bytes = len.div_ceil(8) as usize;
+        let addr = match afi {
+            Afi::Ipv4 => {
+                if len > 32 {
+                    return Err(Error::BadPrefixLength(len));
+                }
+                let mut bytes = [0u8; 4];
+                reader.read_exact(&mut bytes[..len_bytes])?;
+                IpAddr::V4(Ipv4Addr::from(bytes))
+            }
+            Afi::Ipv6 => {
+                if len > 128 {
+                    return Err(Error::BadPrefixLength(len));
+                }
+                let mut bytes = [0u8; 16];
+                reader.read_exact(&mut bytes[..len_bytes])?;
+                IpAddr::V6(Ipv6Addr::from(bytes))
+            }
+        };
+        Ok(Prefix { addr, len })
+    }
+
+    /// Read the prefixes packed in the next `length` bytes, as in the
+    /// withdrawn routes and NLRI fields of a BGP UPDATE
+    pub fn list_from_reader<R: Read>(
+        reader: &mut R,
+        afi: Afi,
+        length: u64,
+    ) -> Result<Vec<Self>, Error> {
+        let mut reader = reader.take(length);
+        let mut prefixes = Vec::new();
+        while reader.limit() > 0 {
+            prefixes.push(Prefix::from_reader(&mut reader, afi)?);
+        }
+        Ok(prefixes)
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+impl Serialize for Prefix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl fmt::Display for RouteDistinguisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rd = &self.0;
+        let rd_type = u16::from_be_bytes([rd[0], rd[1]]);
+        match rd_type {
+            0 => write!(
+                f,
+                "{}:{}",
+                u16::from_be_bytes([rd[2], rd[3]]),
+                u32::from_be_bytes([rd[4], rd[5], rd[6], rd[7]])
+            ),
+            1 => write!(
+                f,
+                "{}:{}",
+                Ipv4Addr::new(rd[2], rd[3], rd[4], rd[5]),
+                u16::from_be_bytes([rd[6], rd[7]])
+            ),
+            2 => write!(
+                f,
+                "{}:{}",
+                u32::from_be_bytes([rd[2], rd[3], rd[4], rd[5]]),
+                u16::from_be_bytes([rd[6], rd[7]])
+            ),
+            _ => write!(f, "{}", hex::encode(rd)),
+        }
+    }
+}
+
+impl Serialize for RouteDistinguisher {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Nlri {
+    /// IP prefix of the route, None for the NLRI we don't decode
+    pub fn prefix(&self) -> Option<Prefix> {
+        match self {
+            Nlri::Prefix { prefix } | Nlri::Labeled { prefix, .. } | Nlri::Vpn { prefix, .. } => {
+                Some(*prefix)
+            }
+            Nlri::Raw { .. } => None,
+        }
+    }
+
+    /// Read a single NLRI entry (RFC 4760 encoding) for the given AFI/SAFI.
+    /// The reader must end with the NLRI field: an unknown AFI/SAFI takes all
+    /// of it
+    pub fn from_reader<R: Read>(reader: &mut R, afi: u16, safi: u8) -> Result<Self, Error> {
+        match (Afi::from_repr(afi), Safi::from_repr(safi)) {
+            (Some(afi), Some(Safi::Unicast | Safi::Multicast)) => Ok(Nlri::Prefix {
+                prefix: Prefix::from_reader(reader, afi)?,
+            }),
+            (Some(afi), Some(Safi::MplsLabel)) => {
+                let len = reader.read_u8()?;
+                let (labels, len) = read_labels(reader, len)?;
+                let prefix = Prefix::from_reader_with_len(reader, afi, len)?;
+                Ok(Nlri::Labeled { labels, prefix })
+            }
+            (Some(afi), Some(Safi::MplsVpn | Safi::MulticastMplsVpn)) => {
+                let len = reader.read_u8()?;
+                let (labels, len) = read_labels(reader, len)?;
+                let len = len.checked_sub(64).ok_or(Error::BadPrefixLength(len))?;
+                let mut rd = [0u8; 8];
+                reader.read_exact(&mut rd)?;
+                let prefix = Prefix::from_reader_with_len(reader, afi, len)?;
+                Ok(Nlri::Vpn {
+                    labels,
+                    rd: RouteDistinguisher(rd),
+                    prefix,
+                })
+            }
+            _ => {
+                // VPLS, EVPN, flowspec... each have their own length encoding,
+                // the rest of the reader is kept as is rather than guessed
+                let mut data = Vec::new();
+                reader.read_to_end(&mut data)?;
+                Ok(Nlri::Raw { data })
+            }
+        }
+    }
+}
+
+/// Read the label stack (RFC 8277) in front of a labeled prefix, returns the
+/// labels and the number of bits left for the rest of the NLRI
+fn read_labels<R: Read>(reader: &mut R, len: u8) -> Result<(Vec<u32>, u8), Error> {
+    let mut labels = Vec::new();
+    let mut remaining = len;
+    loop {
+        remaining = remaining
+            .checked_sub(24)
+            .ok_or(Error::BadPrefixLength(len))?;
+        let label = reader.read_u24::<BigEndian>()?;
+        labels.push(label >> 4);
+        // Stop at the bottom of the stack or at the withdrawn/compatibility label
+        if label & 0x01 == 0x01 || label == 0x800000 || label == 0 {
+            break;
+        }
+    }
+    Ok((labels, remaining))
+}
+
+impl fmt::Display for Nlri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels_to_string = |labels: &Vec<u32>| {
+            labels
+                .iter()
+                .map(|label| label.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        match self {
+            Nlri::Prefix { prefix } => write!(f, "{}", prefix),
+            Nlri::Labeled { labels, prefix } => {
+                write!(f, "{} LABEL {}", prefix, labels_to_string(labels))
+            }
+            Nlri::Vpn { labels, rd, prefix } => {
+                write!(f, "RD {} {} LABEL {}", rd, prefix, labels_to_string(labels))
+            }
+            Nlri::Raw { data } => write!(f, "{}", hex::encode(data)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reading_prefix() {
+        let mut cursor = Cursor::new(vec![0x18, 0xc0, 0x00, 0x02]);
+        let prefix = Prefix::from_reader(&mut cursor, Afi::Ipv4).unwrap();
+        assert_eq!(prefix.to_string(), "192.0.2.0/24");
+        let mut cursor = Cursor::new(vec![0x21, 0xc0, 0x00, 0x02, 0x00, 0x00]);
+        let prefix = Prefix::from_reader(&mut cursor, Afi::Ipv4);
+        assert!(matches!(prefix.unwrap_err(), Error::BadPrefixLength(33)));
+    }
+
+    #[test]
+    fn test_reading_prefix_list() {
+        let mut cursor = Cursor::new(vec![
+            0x18, 0xc0, 0x00, 0x02, // 192.0.2.0/24
+            0x08, 0x0a, // 10.0.0.0/8
+            0x00, // 0.0.0.0/0
+            0xff, // not part of the list
+        ]);
+        let prefixes = Prefix::list_from_reader(&mut cursor, Afi::Ipv4, 7).unwrap();
+        let prefixes = prefixes.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(prefixes, vec!["192.0.2.0/24", "10.0.0.0/8", "0.0.0.0/0"]);
+        assert_eq!(cursor.position(), 7);
+    }
+
+    #[test]
+    fn test_reading_labeled_nlri() {
+        let mut cursor = Cursor::new(vec![
+            0x30, // length 24 + 24 bits
+            0x00, 0x06, 0x41, // label 100, bottom of stack
+            0x0a, 0x00, 0x00, // 10.0.0.0/24
+        ]);
+        let nlri = Nlri::from_reader(&mut cursor, 1, 4).unwrap();
+        assert_eq!(nlri.to_string(), "10.0.0.0/24 LABEL 100");
+    }
+
+    #[test]
+    fn test_reading_vpn_nlri() {
+        let mut cursor = Cursor::new(vec![
+            0x70, // length 24 + 64 + 24 bits
+            0x00, 0x06, 0x41, // label 100, bottom of stack
+            0x00, 0x00, 0xfd, 0xe8, 0x00, 0x00, 0x00, 0x01, // RD 65000:1
+            0x0a, 0x00, 0x00, // 10.0.0.0/24
+        ]);
+        let nlri = Nlri::from_reader(&mut cursor, 1, 128).unwrap();
+        assert_eq!(nlri.to_string(), "RD 65000:1 10.0.0.0/24 LABEL 100");
+        let json = serde_json::to_value(&nlri).unwrap();
+        assert_eq!(json["type"], "vpn");
+        assert_eq!(json["rd"], "65000:1");
+        assert_eq!(json["prefix"], "10.0.0.0/24");
+    }
+
+    #[test]
+    fn test_reading_unknown_nlri() {
+        // two VPLS NLRI (RFC 4761), with a 2-byte length in bytes
+        let data = vec![
+            0x00, 0x02, 0xab, 0xcd, // first NLRI
+            0x00, 0x01, 0xef, // second NLRI
+        ];
+        let mut cursor = Cursor::new(data.clone());
+        let nlri = Nlri::from_reader(&mut cursor, 25, 65).unwrap();
+        assert_eq!(nlri, Nlri::Raw { data });
+        assert_eq!(nlri.to_string(), "0002abcd0001ef");
+    }
+}
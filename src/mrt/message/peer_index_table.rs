@@ -1,18 +1,18 @@
+use super::geo_peer_table::{GeoLocation, GeoPeerTable};
 use crate::mrt::Error;
 use byteorder::{BigEndian, ReadBytesExt};
 use std::io::Read;
 use std::net::{IpAddr, IpAddr::V4, IpAddr::V6, Ipv4Addr, Ipv6Addr};
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct PeerEntry {
-    pub bgp_id: u32,     // BGP ID of the peer
-    pub peer_ip: IpAddr, // IP address of the peer
-    pub peer_asn: u32,   // Autonomous System Number of the peer
+    pub bgp_id: u32,                   // BGP ID of the peer
+    pub peer_ip: IpAddr,               // IP address of the peer
+    pub peer_asn: u32,                 // Autonomous System Number of the peer
+    pub location: Option<GeoLocation>, // Set from the GEO_PEER_TABLE, if any
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct PeerIndexTable {
     pub collector_bgp_id: u32, // BGP ID of the collector
     pub view_name_len: u16,    // Length of the view name
@@ -43,6 +43,13 @@ impl PeerIndexTable {
             entries,
         })
     }
+
+    /// Attach the locations of a GEO_PEER_TABLE to the peers
+    pub fn set_locations(&mut self, geo_peer_table: &GeoPeerTable) {
+        for entry in &mut self.entries {
+            entry.location = geo_peer_table.peer_location(entry);
+        }
+    }
 }
 
 impl PeerEntry {
@@ -70,6 +77,7 @@ impl PeerEntry {
             bgp_id,
             peer_ip,
             peer_asn,
+            location: None,
         })
     }
 }
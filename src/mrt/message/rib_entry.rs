@@ -1,42 +1,42 @@
-use super::{
-    BgpAggregator, BgpAsPath, BgpAttributeHeader, BgpAttributeType, BgpCommunity,
-    BgpLargeCommunity, BgpMultiExitDisc, BgpNextHop, BgpOrigin, PeerIndexTable,
-};
+use super::geo_peer_table::GeoLocation;
+use super::{BgpAttributes, CsvRecord, PeerIndexTable};
 use crate::mrt::Error;
 use byteorder::{BigEndian, ReadBytesExt};
 use chrono::DateTime;
 use core::net;
 use serde::Serialize;
-use serde_with::{DisplayFromStr, serde_as, skip_serializing_none};
-use std::io::{Read, copy, sink};
+use serde_with::{serde_as, skip_serializing_none};
+use std::fmt::{self, Display};
+use std::io::Read;
 
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct RibEntry {
-    pub peer_index: u16,
+    pub peer_index: Option<u16>, // Index in the peer index table, TABLE_DUMP_V2 only
+    pub path_id: Option<u32>,    // ADD-PATH path identifier (RFC 8050)
     pub peer_asn: u32,
     pub peer_ip: net::IpAddr,
+    pub peer_location: Option<GeoLocation>, // From the GEO_PEER_TABLE, TABLE_DUMP_V2 only
     pub originated_time: DateTime<chrono::Utc>,
     pub attribute_length: u16,
-    #[serde_as(as = "Option<DisplayFromStr>")]
-    pub bgp_origin: Option<BgpOrigin>,
-    pub bgp_as_path: Option<BgpAsPath>,
-    pub bgp_next_hop: Option<BgpNextHop>,
-    pub bgp_community: Option<BgpCommunity>,
-    pub bgp_large_community: Option<BgpLargeCommunity>,
-    pub bgp_multi_exit_disc: Option<BgpMultiExitDisc>,
-    pub bgp_aggregator: Option<BgpAggregator>,
+    #[serde(flatten)]
+    pub attributes: BgpAttributes,
 }
 
 impl RibEntry {
     pub fn from_reader<R: Read>(
         reader: &mut R,
         peer_index_table: &PeerIndexTable,
+        add_path: bool,
     ) -> Result<Self, Error> {
         let peer_index = reader.read_u16::<BigEndian>()?;
         let originated_time = reader.read_u32::<BigEndian>()?;
+        let path_id = if add_path {
+            Some(reader.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
         let originated_time =
             DateTime::from_timestamp(originated_time.into(), 0).ok_or(Error::BadRibEntryHeader)?;
         let attribute_length = reader.read_u16::<BigEndian>()?;
@@ -46,61 +46,116 @@ impl RibEntry {
             return Err(Error::InvalidPeerIndex(peer_index));
         }
 
-        // Create a new RibEntry instance
-        let mut rib_entry = RibEntry {
-            peer_index,
+        let attributes = BgpAttributes::from_reader(reader, attribute_length, true)?;
+        Ok(RibEntry {
+            peer_index: Some(peer_index),
+            path_id,
             originated_time,
             attribute_length,
             peer_asn: peer_index_table.entries[peer_index as usize].peer_asn,
             peer_ip: peer_index_table.entries[peer_index as usize].peer_ip,
-            bgp_origin: None,
-            bgp_as_path: None,
-            bgp_next_hop: None,
-            bgp_community: None,
-            bgp_large_community: None,
-            bgp_multi_exit_disc: None,
-            bgp_aggregator: None,
-        };
+            peer_location: peer_index_table.entries[peer_index as usize].location,
+            attributes,
+        })
+    }
+
+    /// Global next hop, from MP_REACH_NLRI when present else from NEXT_HOP
+    pub fn next_hop(&self) -> Option<net::IpAddr> {
+        self.attributes
+            .bgp_mp_reach_nlri
+            .as_ref()
+            .and_then(|mp_reach| mp_reach.next_hop)
+            .or_else(|| {
+                self.attributes
+                    .bgp_next_hop
+                    .as_ref()
+                    .map(|next_hop| next_hop.0.into())
+            })
+    }
+
+    /// IPv6 link-local next hop of MP_REACH_NLRI, if any
+    pub fn next_hop_link_local(&self) -> Option<net::Ipv6Addr> {
+        self.attributes
+            .bgp_mp_reach_nlri
+            .as_ref()
+            .and_then(|mp_reach| mp_reach.next_hop_link_local)
+    }
+
+    /// Build the bgpdump-like `-m` CSV record of this entry for the given prefix
+    pub fn to_csv_record(
+        &self,
+        record_type: &str,
+        datetime: DateTime<chrono::Utc>,
+        prefix_with_len: String,
+        safi: String,
+    ) -> CsvRecord {
+        CsvRecord {
+            record_type: record_type.to_string(),
+            datetime,
+            entry_type: "B".to_string(),
+            peer_ip: Some(self.peer_ip),
+            peer_asn: Some(self.peer_asn),
+            prefix_with_len,
+            as_path: self.attributes.as_path_string(),
+            bgp_origin: self.attributes.origin_string(),
+            safi,
+            path_id: self.path_id,
+            local_pref: self.attributes.bgp_local_pref.as_ref().map(|pref| pref.0),
+            originator_id: self.attributes.bgp_originator_id.as_ref().map(|id| id.0),
+            cluster_list: self.attributes.cluster_list_string(),
+            atomic_aggregate: self.attributes.atomic_aggregate_string(),
+            otc: self.attributes.bgp_otc.as_ref().map(|otc| otc.0),
+            error_action: self
+                .attributes
+                .error_action
+                .map(|action| action.to_string())
+                .unwrap_or_default(),
+            peer_latitude: self.peer_location.map(|location| location.latitude),
+            peer_longitude: self.peer_location.map(|location| location.longitude),
+        }
+    }
+}
+
+/// Records holding routes with possibly malformed attributes (RFC 7606)
+pub trait MalformedRoutes {
+    /// Record the RFC 7606 action of the malformed routes in the output
+    fn mark_malformed(&mut self);
 
-        // loop over all attributes
-        let mut offset: u16 = 0;
-        while offset < rib_entry.attribute_length {
-            let header = BgpAttributeHeader::from_reader(reader)?;
-            offset += header.offset;
-            match header.attribute_type {
-                BgpAttributeType::Origin => {
-                    rib_entry.bgp_origin = BgpOrigin::from_reader(reader).ok();
-                }
-                BgpAttributeType::AsPath => {
-                    rib_entry.bgp_as_path = BgpAsPath::from_reader(reader).ok();
-                }
-                BgpAttributeType::NextHop => {
-                    rib_entry.bgp_next_hop = BgpNextHop::from_reader(reader).ok();
-                }
-                BgpAttributeType::Community => {
-                    rib_entry.bgp_community =
-                        BgpCommunity::from_reader(reader, header.attribute_length).ok();
-                }
-                BgpAttributeType::LargeCommunity => {
-                    rib_entry.bgp_large_community =
-                        BgpLargeCommunity::from_reader(reader, header.attribute_length).ok();
-                }
-                BgpAttributeType::MultiExitDisc => {
-                    rib_entry.bgp_multi_exit_disc = BgpMultiExitDisc::from_reader(reader).ok();
-                }
-                BgpAttributeType::Aggregator => {
-                    rib_entry.bgp_aggregator = BgpAggregator::from_reader(reader).ok();
-                }
-                _ => {
-                    // skip unimplemented attributes
-                    copy(
-                        &mut reader.take(header.attribute_length.into()),
-                        &mut sink(),
-                    )?;
-                }
-            }
+    /// Apply the RFC 7606 actions like a router would, returns false when
+    /// nothing is left of the record
+    fn drop_malformed(&mut self) -> bool;
+}
+
+/// The entries of a RIB record, those a router would withdraw are dropped
+impl MalformedRoutes for Vec<RibEntry> {
+    fn mark_malformed(&mut self) {
+        for entry in self {
+            entry.attributes.mark_error_action();
+        }
+    }
+
+    fn drop_malformed(&mut self) -> bool {
+        self.retain_mut(|entry| entry.attributes.drop_malformed());
+        !self.is_empty()
+    }
+}
+
+impl Display for RibEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FROM: {} AS {}", self.peer_ip, self.peer_asn)?;
+        if let Some(path_id) = self.path_id {
+            writeln!(f, "PATH_ID: {}", path_id)?;
+        }
+        writeln!(
+            f,
+            "ORIGINATED: {}",
+            self.originated_time.format("%Y-%m-%d %H:%M:%S")
+        )?;
+        if f.alternate() {
+            write!(f, "{:#}", self.attributes)
+        } else {
+            write!(f, "{}", self.attributes)
         }
-        Ok(rib_entry)
     }
 }
 
@@ -110,7 +165,9 @@ impl RibEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mrt::message::bgp_attribute::{BgpOrigin, BgpOriginType};
+    use crate::mrt::message::bgp_attribute::{
+        BgpCommunity, BgpLargeCommunity, BgpMultiExitDisc, BgpNextHop, BgpOrigin, BgpOriginType,
+    };
     use crate::mrt::message::peer_index_table::{PeerEntry, PeerIndexTable};
     use std::io::Cursor;
     use std::net::Ipv4Addr;
@@ -121,6 +178,7 @@ mod tests {
             bgp_id: 0,
             peer_ip: net::IpAddr::V4(net::Ipv4Addr::new(192, 0, 2, 1)),
             peer_asn: 65536,
+            location: None,
         }];
 
         let peer_index_table = PeerIndexTable {
@@ -134,15 +192,15 @@ mod tests {
         let mut cursor = Cursor::new(vec![
             0x00, 0x00, // Peer index
             0x00, 0x00, 0x00, 0x01, // Originated time
-            0x00, 0x3d, // attributes length 61
+            0x00, 0x44, // attributes length 68
             0x10, 0x01, 0x00, 0x01, // BGP Header type=1 (origin) length=1
             0x00, // Origin IGP
             0x10, 0x02, 0x00, 0x0a, // BGP Header type=2 (aspath) length=10
-            0x00, // AS Path Segment Type
+            0x02, // AS Path Segment Type
             0x02, // AS Path Segment Length
             0x00, 0x00, 0x00, 0x01, // AS Path Segment 1
             0x00, 0x00, 0x00, 0x02, // AS Path Segment 2
-            0x10, 0x23, 0x00, 0x01, // BGP Header type=35 (wrong) length=1
+            0x10, 0x23, 0x00, 0x01, // BGP Header type=35 (OTC) bad length=1
             0x00, // No data
             0x10, 0x03, 0x00, 0x04, // BGP Header type=3 (next_hop) length=4
             0xC0, 0x00, 0x02, 0x01, // Next Hop IP
@@ -157,39 +215,112 @@ mod tests {
             0x00, 0x00, 0x00, 0x01,
         ]);
 
-        let rib_entry = RibEntry::from_reader(&mut cursor, &peer_index_table);
+        let rib_entry = RibEntry::from_reader(&mut cursor, &peer_index_table, false);
         assert!(rib_entry.is_ok());
         let rib_entry = rib_entry.unwrap();
         assert!(matches!(
-            rib_entry.bgp_origin,
+            rib_entry.attributes.bgp_origin,
             Some(BgpOrigin(BgpOriginType::Igp))
         ));
 
-        let segments = rib_entry.bgp_as_path.unwrap().segments;
-        let expected_segments = vec![1, 2];
-        assert_eq!(segments, expected_segments);
+        // OTC with a bad length is dropped without desyncing the attributes
+        assert!(rib_entry.attributes.bgp_otc.is_none());
+        assert_eq!(rib_entry.attributes.attribute_errors[0].type_code, 35);
+
+        let segments = rib_entry.attributes.bgp_as_path.unwrap().segments;
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].asns, vec![1, 2]);
 
         let _expected_next_hop = BgpNextHop(Ipv4Addr::new(192, 0, 2, 1));
         assert!(matches!(
-            rib_entry.bgp_next_hop.unwrap(),
+            rib_entry.attributes.bgp_next_hop.unwrap(),
             _expected_next_hop
         ));
 
         let _expected_community = BgpCommunity(vec![(1, 2), (3, 4)]);
         assert!(matches!(
-            rib_entry.bgp_community.unwrap(),
+            rib_entry.attributes.bgp_community.unwrap(),
             _expected_community
         ));
 
         let _expected_large_community = BgpLargeCommunity(vec![(1, 2, 3)]);
         assert!(matches!(
-            rib_entry.bgp_large_community.unwrap(),
+            rib_entry.attributes.bgp_large_community.unwrap(),
             _expected_large_community
         ));
         let _expected_multi_exit_disc = BgpMultiExitDisc(1);
         assert!(matches!(
-            rib_entry.bgp_multi_exit_disc.unwrap(),
+            rib_entry.attributes.bgp_multi_exit_disc.unwrap(),
             _expected_multi_exit_disc
         ));
     }
+
+    #[test]
+    fn test_rib_entry_add_path() {
+        let peer_index_table = PeerIndexTable {
+            collector_bgp_id: 0,
+            view_name_len: 0,
+            view_name: "test".to_string(),
+            nentries: 1,
+            entries: vec![PeerEntry {
+                bgp_id: 0,
+                peer_ip: net::IpAddr::V4(net::Ipv4Addr::new(192, 0, 2, 1)),
+                peer_asn: 65536,
+                location: None,
+            }],
+        };
+
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, // Peer index
+            0x00, 0x00, 0x00, 0x01, // Originated time
+            0x00, 0x00, 0x00, 0x07, // Path identifier
+            0x00, 0x04, // attributes length 4
+            0x40, 0x01, 0x01, 0x00, // Origin IGP
+        ]);
+
+        let rib_entry = RibEntry::from_reader(&mut cursor, &peer_index_table, true).unwrap();
+        assert_eq!(rib_entry.path_id, Some(7));
+        assert!(rib_entry.to_string().contains("PATH_ID: 7\n"));
+        let json = serde_json::to_value(&rib_entry).unwrap();
+        assert_eq!(json["path_id"], 7);
+    }
+
+    #[test]
+    fn test_rib_entry_ipv6_next_hops() {
+        let peer_index_table = PeerIndexTable {
+            collector_bgp_id: 0,
+            view_name_len: 0,
+            view_name: "test".to_string(),
+            nentries: 1,
+            entries: vec![PeerEntry {
+                bgp_id: 0,
+                peer_ip: "2001:db8::1".parse().unwrap(),
+                peer_asn: 65000,
+                location: None,
+            }],
+        };
+
+        let mut data = vec![
+            0x00, 0x00, // Peer index
+            0x00, 0x00, 0x00, 0x01, // Originated time
+            0x00, 0x24, // attributes length 36
+            0x80, 0x0e, 0x21, // MP_REACH_NLRI, length 33
+            0x20, // next hop length
+        ];
+        data.extend_from_slice(&"2001:db8::1".parse::<net::Ipv6Addr>().unwrap().octets());
+        data.extend_from_slice(&"fe80::1".parse::<net::Ipv6Addr>().unwrap().octets());
+
+        let rib_entry =
+            RibEntry::from_reader(&mut Cursor::new(data), &peer_index_table, false).unwrap();
+        assert_eq!(rib_entry.next_hop(), Some("2001:db8::1".parse().unwrap()));
+        assert_eq!(
+            rib_entry.next_hop_link_local(),
+            Some("fe80::1".parse().unwrap())
+        );
+        assert!(
+            rib_entry
+                .to_string()
+                .ends_with("NEXT_HOP: 2001:db8::1\nNEXT_HOP: fe80::1\n")
+        );
+    }
 }
@@ -0,0 +1,265 @@
+use super::{
+    CsvRecord, MalformedRoutes, Nlri, PeerIndexTable, RibEntry, Safi, WriteRecords,
+    bgp_attribute::afi_safi_name,
+};
+use crate::mrt::Error;
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_with::serde_as;
+use std::fmt::{self, Display};
+use std::io::{Read, Write};
+
+/// RIB_GENERIC record, any AFI/SAFI with a single NLRI per record
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct RibGeneric {
+    time: DateTime<chrono::Utc>,
+    sequence_number: u32,       // Sequence number of the RIB entry
+    afi: u16,                   // Address Family Identifier
+    safi: u8,                   // Subsequent Address Family Identifier
+    nlri: Nlri,                 // Decoded NLRI, hex when the AFI/SAFI is unknown
+    entry_count: Option<u16>,   // Number of entries, None when the NLRI isn't decoded
+    rib_entries: Vec<RibEntry>, // Rib entries
+    #[serde(skip)]
+    add_path: bool, // ADD-PATH subtype, entries carry a path identifier
+}
+
+impl RibGeneric {
+    pub fn from_reader<R: Read>(
+        reader: &mut R,
+        peer_index_table: &PeerIndexTable,
+        time: DateTime<chrono::Utc>,
+        add_path: bool,
+    ) -> Result<Self, Error> {
+        let sequence_number = reader.read_u32::<BigEndian>()?;
+        let afi = reader.read_u16::<BigEndian>()?;
+        let safi = reader.read_u8()?;
+        let nlri = Nlri::from_reader(reader, afi, safi)?;
+        // The NLRI of an unknown AFI/SAFI takes the rest of the record, its
+        // entries included, as the end of the NLRI can't be told
+        let entry_count = match nlri {
+            Nlri::Raw { .. } => None,
+            _ => Some(reader.read_u16::<BigEndian>()?),
+        };
+        // read the rib entry
+        let mut rib_entries: Vec<RibEntry> =
+            Vec::with_capacity(entry_count.unwrap_or_default().into());
+        for _ in 0..entry_count.unwrap_or_default() {
+            let entry = RibEntry::from_reader(reader, peer_index_table, add_path)?;
+            rib_entries.push(entry);
+        }
+        Ok(RibGeneric {
+            time,
+            sequence_number,
+            afi,
+            safi,
+            nlri,
+            entry_count,
+            rib_entries,
+            add_path,
+        })
+    }
+
+    /// Name of the AFI/SAFI pair, e.g. IPV4_MPLS_VPN or AFI25_SAFI65
+    pub fn afi_safi_name(&self) -> String {
+        afi_safi_name(self.afi, self.safi)
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    /// Address Family Identifier, not limited to the ones of [`super::Afi`]
+    pub fn afi(&self) -> u16 {
+        self.afi
+    }
+
+    /// Subsequent Address Family Identifier, not limited to the ones of
+    /// [`Safi`]
+    pub fn safi(&self) -> u8 {
+        self.safi
+    }
+
+    pub fn nlri(&self) -> &Nlri {
+        &self.nlri
+    }
+
+    /// False when the NLRI of an unknown AFI/SAFI took the rest of the
+    /// record: its entries are left undecoded in the raw NLRI
+    pub fn has_entries_decoded(&self) -> bool {
+        self.entry_count.is_some()
+    }
+
+    pub fn rib_entries(&self) -> &[RibEntry] {
+        &self.rib_entries
+    }
+
+    pub fn into_rib_entries(self) -> Vec<RibEntry> {
+        self.rib_entries
+    }
+
+    /// Whether the entries carry an ADD-PATH path identifier
+    pub fn is_add_path(&self) -> bool {
+        self.add_path
+    }
+}
+
+impl WriteRecords for RibGeneric {
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(b'|')
+            .has_headers(false)
+            .from_writer(writer);
+        let safi = Safi::from_repr(self.safi)
+            .map(|safi| safi.to_string())
+            .unwrap_or_else(|| self.safi.to_string());
+        if !self.has_entries_decoded() {
+            // One line for the whole record, without a peer
+            csv_writer.serialize(CsvRecord {
+                record_type: "TABLE_DUMP2".to_string(),
+                datetime: self.time,
+                entry_type: "B".to_string(),
+                peer_ip: None,
+                peer_asn: None,
+                prefix_with_len: self.nlri.to_string(),
+                as_path: String::new(),
+                bgp_origin: String::new(),
+                safi: safi.clone(),
+                path_id: None,
+                local_pref: None,
+                originator_id: None,
+                cluster_list: String::new(),
+                atomic_aggregate: String::new(),
+                otc: None,
+                error_action: String::new(),
+                peer_latitude: None,
+                peer_longitude: None,
+            })?;
+        }
+        for entry in &self.rib_entries {
+            csv_writer.serialize(entry.to_csv_record(
+                "TABLE_DUMP2",
+                self.time,
+                self.nlri.to_string(),
+                safi.clone(),
+            ))?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        writeln!(writer, "{}", json)?;
+        Ok(())
+    }
+}
+
+impl MalformedRoutes for RibGeneric {
+    fn mark_malformed(&mut self) {
+        self.rib_entries.mark_malformed();
+    }
+
+    fn drop_malformed(&mut self) -> bool {
+        self.rib_entries.drop_malformed()
+    }
+}
+
+impl RibGeneric {
+    fn fmt_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TIME: {}\nTYPE: TABLE_DUMP_V2/{}{}\nPREFIX: {}\nSEQUENCE: {}\n",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.afi_safi_name(),
+            if self.add_path { "_ADDPATH" } else { "" },
+            self.nlri,
+            self.sequence_number,
+        )
+    }
+}
+
+impl Display for RibGeneric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.has_entries_decoded() {
+            self.fmt_header(f)?;
+            return writeln!(f, "ENTRIES: not decoded, unknown AFI/SAFI");
+        }
+        for entry in &self.rib_entries {
+            self.fmt_header(f)?;
+            if f.alternate() {
+                writeln!(f, "{:#}", entry)?;
+            } else {
+                writeln!(f, "{}", entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_rib_generic_unknown_afi_safi() {
+        let peer_index_table = PeerIndexTable {
+            collector_bgp_id: 0,
+            view_name_len: 0,
+            view_name: String::new(),
+            nentries: 0,
+            entries: vec![],
+        };
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x01, // sequence number
+            0x00, 0x19, // AFI 25 (L2VPN)
+            0x41, // SAFI 65 (VPLS)
+            0x00, 0x11, // NLRI length 17 (RFC 4761)
+            0x00, 0x00, 0xfd, 0xe8, 0x00, 0x00, 0x00, 0x01, // RD 65000:1
+            0x00, 0x01, // VE ID
+            0x00, 0x01, // VE block offset
+            0x00, 0x0a, // VE block size
+            0x01, 0x00, 0x01, // label base
+            0x00, 0x00, // entry count
+        ]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibGeneric::from_reader(&mut cursor, &peer_index_table, time, false).unwrap();
+        assert_eq!(rib.afi_safi_name(), "AFI25_SAFI65");
+        // the rest of the record is kept, entry count included
+        assert_eq!(
+            rib.nlri.to_string(),
+            "00110000fde80000000100010001000a0100010000"
+        );
+        assert!(!rib.has_entries_decoded());
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+
+        // the record is still written out, with its NLRI in hex
+        let hex = "00110000fde80000000100010001000a0100010000";
+        assert_eq!(
+            rib.to_string(),
+            format!(
+                "TIME: 1970-01-01 00:00:00\nTYPE: TABLE_DUMP_V2/AFI25_SAFI65\nPREFIX: {}\n\
+                 SEQUENCE: 1\nENTRIES: not decoded, unknown AFI/SAFI\n",
+                hex
+            )
+        );
+        let mut csv = Vec::new();
+        rib.write_csv_records(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "TABLE_DUMP2|1970-01-01T00:00:00Z|B|||{}|||65|||||||||\n",
+                hex
+            )
+        );
+        let json = serde_json::to_value(&rib).unwrap();
+        assert_eq!(json["nlri"]["data"], hex);
+        assert!(json["entry_count"].is_null());
+    }
+}
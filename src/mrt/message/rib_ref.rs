@@ -0,0 +1,201 @@
+use super::{Afi, BgpAttributesRef, PeerEntry, PeerIndexTable, Prefix, RibEntry};
+use crate::mrt::Error;
+use chrono::DateTime;
+use std::io;
+
+/// RIB_IPV4_* and RIB_IPV6_* records borrowed from the input, the entries
+/// are only split on access and their attributes decoded on demand
+#[derive(Debug, Clone, Copy)]
+pub struct RibRef<'a> {
+    time: DateTime<chrono::Utc>,
+    sequence_number: u32, // Sequence number of the RIB entry
+    prefix: Prefix,       // network prefix
+    entry_count: u16,     // Number of entries in the RIB
+    entries: &'a [u8],    // Rib entries as found on the wire
+    add_path: bool,       // ADD-PATH subtype, entries carry a path identifier
+}
+
+/// One RIB entry borrowed from the input
+#[derive(Debug, Clone, Copy)]
+pub struct RibEntryRef<'a> {
+    pub peer_index: u16, // Index in the peer index table
+    pub originated_time: DateTime<chrono::Utc>,
+    pub path_id: Option<u32>, // ADD-PATH path identifier (RFC 8050)
+    pub attributes: BgpAttributesRef<'a>,
+}
+
+/// Iterator over the entries of a [`RibRef`], stops after the first entry
+/// running past the end of the record
+#[derive(Debug, Clone)]
+pub struct RibEntryRefIter<'a> {
+    data: &'a [u8],
+    remaining: u16, // Entries left according to the entry count
+    add_path: bool,
+}
+
+impl<'a> RibRef<'a> {
+    pub fn from_bytes(
+        mut data: &'a [u8],
+        time: DateTime<chrono::Utc>,
+        afi: Afi,
+        add_path: bool,
+    ) -> Result<Self, Error> {
+        let sequence_number = u32::from_be_bytes(split_array(&mut data)?);
+        let prefix = Prefix::from_reader(&mut data, afi)?;
+        let entry_count = u16::from_be_bytes(split_array(&mut data)?);
+        Ok(RibRef {
+            time,
+            sequence_number,
+            prefix,
+            entry_count,
+            entries: data,
+            add_path,
+        })
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    pub fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    pub fn entry_count(&self) -> u16 {
+        self.entry_count
+    }
+
+    /// Whether the entries carry an ADD-PATH path identifier
+    pub fn is_add_path(&self) -> bool {
+        self.add_path
+    }
+
+    pub fn entries(&self) -> RibEntryRefIter<'a> {
+        RibEntryRefIter {
+            data: self.entries,
+            remaining: self.entry_count,
+            add_path: self.add_path,
+        }
+    }
+}
+
+impl<'a> RibEntryRef<'a> {
+    fn from_bytes(data: &mut &'a [u8], add_path: bool) -> Result<Self, Error> {
+        let peer_index = u16::from_be_bytes(split_array(data)?);
+        let originated_time = u32::from_be_bytes(split_array(data)?);
+        let originated_time =
+            DateTime::from_timestamp(originated_time.into(), 0).ok_or(Error::BadRibEntryHeader)?;
+        let path_id = if add_path {
+            Some(u32::from_be_bytes(split_array(data)?))
+        } else {
+            None
+        };
+        let attribute_length = u16::from_be_bytes(split_array(data)?);
+        let (attributes, rest) = data
+            .split_at_checked(attribute_length.into())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        *data = rest;
+        Ok(RibEntryRef {
+            peer_index,
+            originated_time,
+            path_id,
+            // TABLE_DUMP_V2 always encodes AS_PATH with 4-byte ASNs
+            attributes: BgpAttributesRef::new(attributes, true),
+        })
+    }
+
+    /// Peer of the entry in the table the record comes with
+    pub fn peer<'t>(&self, peer_index_table: &'t PeerIndexTable) -> Result<&'t PeerEntry, Error> {
+        peer_index_table
+            .entries
+            .get(self.peer_index as usize)
+            .ok_or(Error::InvalidPeerIndex(self.peer_index))
+    }
+
+    /// Decode the entry and its attributes, like `RibEntry::from_reader`
+    pub fn decode(&self, peer_index_table: &PeerIndexTable) -> Result<RibEntry, Error> {
+        let peer = self.peer(peer_index_table)?;
+        Ok(RibEntry {
+            peer_index: Some(self.peer_index),
+            path_id: self.path_id,
+            peer_asn: peer.peer_asn,
+            peer_ip: peer.peer_ip,
+            peer_location: peer.location,
+            originated_time: self.originated_time,
+            attribute_length: self.attributes.as_bytes().len() as u16,
+            attributes: self.attributes.decode()?,
+        })
+    }
+}
+
+impl<'a> Iterator for RibEntryRefIter<'a> {
+    type Item = Result<RibEntryRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let entry = RibEntryRef::from_bytes(&mut self.data, self.add_path);
+        if entry.is_err() {
+            self.remaining = 0;
+        }
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining.into()))
+    }
+}
+
+/// Split the first `N` bytes off `data`
+fn split_array<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], Error> {
+    let (bytes, rest) = data
+        .split_first_chunk::<N>()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    *data = rest;
+    Ok(*bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::message::BgpAttributeType;
+    use crate::mrt::test_utils::PEER_INDEX_TABLE;
+
+    #[test]
+    fn test_lazy_rib_entries() {
+        let peer_index_table = PeerIndexTable::from_reader(&mut { &PEER_INDEX_TABLE[..] }).unwrap();
+        let data = [
+            0, 0, 0, 7, // sequence number
+            24, 198, 51, 100, // prefix
+            0, 2, // entry count
+            0, 0, // peer index
+            0x65, 0x53, 0xf1, 0x00, // originated time
+            0, 4, // attribute length
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0, 1, // peer index, not in the table
+            0x65, 0x53, 0xf1, 0x00, // originated time
+            0, 4, // attribute length, cut short
+            0x40, 0x01,
+        ];
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibRef::from_bytes(&data, time, Afi::Ipv4, false).unwrap();
+        assert_eq!(rib.sequence_number(), 7);
+        assert_eq!(rib.prefix().to_string(), "198.51.100.0/24");
+        let mut entries = rib.entries();
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.peer(&peer_index_table).unwrap().peer_asn, 65000);
+        let origin = entry.attributes.get(BgpAttributeType::Origin).unwrap();
+        assert_eq!(origin.value, &[0x00]);
+        let entry = entry.decode(&peer_index_table).unwrap();
+        assert_eq!(entry.attributes.origin_string(), "IGP");
+        assert_eq!(entry.peer_ip.to_string(), "192.0.2.1");
+        assert!(matches!(entries.next().unwrap(), Err(Error::IO(_))));
+        assert!(entries.next().is_none());
+    }
+}
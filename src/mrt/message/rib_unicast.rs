@@ -0,0 +1,277 @@
+use super::Afi;
+use super::MalformedRoutes;
+use super::PeerIndexTable;
+use super::Prefix;
+use super::RibEntry;
+use super::Safi;
+use super::WriteRecords;
+use crate::mrt::Error;
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use serde::Serialize;
+use serde_with::serde_as;
+use std::fmt::{self, Display};
+use std::io::{Read, Write};
+use std::net::IpAddr;
+
+/// RIB_IPV4_UNICAST, RIB_IPV4_MULTICAST, RIB_IPV6_UNICAST and
+/// RIB_IPV6_MULTICAST records, told apart by `afi` and `safi`
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct RibUnicast {
+    time: DateTime<chrono::Utc>,
+    #[serde(skip)]
+    afi: Afi, // IPv4 or IPv6 RIB, the family of the prefix
+    safi: Safi,                 // Unicast or Multicast RIB
+    sequence_number: u32,       // Sequence number of the RIB entry
+    prefix_len: u8,             // Length of the prefix
+    prefix: IpAddr,             // network prefix
+    entry_count: u16,           // Number of entries in the RIB
+    rib_entries: Vec<RibEntry>, // Rib entries
+    #[serde(skip)]
+    add_path: bool, // ADD-PATH subtype, entries carry a path identifier
+}
+
+impl RibUnicast {
+    pub fn from_reader<R: Read>(
+        reader: &mut R,
+        peer_index_table: &PeerIndexTable,
+        time: DateTime<chrono::Utc>,
+        afi: Afi,
+        safi: Safi,
+        add_path: bool,
+    ) -> Result<Self, Error> {
+        let sequence_number = reader.read_u32::<BigEndian>()?;
+        let prefix = Prefix::from_reader(reader, afi)?;
+        // Read the number of entries
+        let entry_count = reader.read_u16::<BigEndian>()?;
+        // read the rib entry
+        let mut rib_entries: Vec<RibEntry> = Vec::with_capacity(entry_count.into());
+        for _ in 0..entry_count {
+            let entry = RibEntry::from_reader(reader, peer_index_table, add_path)?;
+            rib_entries.push(entry);
+        }
+        Ok(RibUnicast {
+            time,
+            afi,
+            safi,
+            sequence_number,
+            prefix_len: prefix.len,
+            prefix: prefix.addr,
+            entry_count,
+            rib_entries,
+            add_path,
+        })
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    /// IPv4 or IPv6 RIB
+    pub fn afi(&self) -> Afi {
+        self.afi
+    }
+
+    /// Unicast or Multicast RIB
+    pub fn safi(&self) -> Safi {
+        self.safi
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    pub fn prefix(&self) -> Prefix {
+        Prefix {
+            addr: self.prefix,
+            len: self.prefix_len,
+        }
+    }
+
+    pub fn rib_entries(&self) -> &[RibEntry] {
+        &self.rib_entries
+    }
+
+    pub fn into_rib_entries(self) -> Vec<RibEntry> {
+        self.rib_entries
+    }
+
+    /// Whether the entries carry an ADD-PATH path identifier
+    pub fn is_add_path(&self) -> bool {
+        self.add_path
+    }
+}
+
+impl WriteRecords for RibUnicast {
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(b'|')
+            .has_headers(false)
+            .from_writer(writer);
+        for entry in &self.rib_entries {
+            csv_writer.serialize(entry.to_csv_record(
+                "TABLE_DUMP2",
+                self.time,
+                format!("{}/{}", self.prefix, self.prefix_len),
+                self.safi.to_string(),
+            ))?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        writeln!(writer, "{}", json)?;
+        Ok(())
+    }
+}
+
+impl MalformedRoutes for RibUnicast {
+    fn mark_malformed(&mut self) {
+        self.rib_entries.mark_malformed();
+    }
+
+    fn drop_malformed(&mut self) -> bool {
+        self.rib_entries.drop_malformed()
+    }
+}
+
+impl Display for RibUnicast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.rib_entries {
+            write!(
+                f,
+                "TIME: {}\nTYPE: TABLE_DUMP_V2/{}_{}{}\nPREFIX: {}/{}\nSEQUENCE: {}\n",
+                self.time.format("%Y-%m-%d %H:%M:%S"),
+                self.afi,
+                self.safi,
+                if self.add_path { "_ADDPATH" } else { "" },
+                self.prefix,
+                self.prefix_len,
+                self.sequence_number,
+            )?;
+            if f.alternate() {
+                writeln!(f, "{:#}", entry)?;
+            } else {
+                writeln!(f, "{}", entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::message::peer_index_table::PeerEntry;
+    use std::io::Cursor;
+
+    fn peer_index_table() -> PeerIndexTable {
+        PeerIndexTable {
+            collector_bgp_id: 0,
+            view_name_len: 0,
+            view_name: String::new(),
+            nentries: 1,
+            entries: vec![PeerEntry {
+                bgp_id: 0,
+                peer_ip: IpAddr::V6("2001:db8::1".parse().unwrap()),
+                peer_asn: 65000,
+                location: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rib_ipv6_unicast_from_reader() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x2a, // sequence number
+            0x20, // prefix length /32
+            0x20, 0x01, 0x0d, 0xb8, // prefix 2001:db8::
+            0x00, 0x01, // entry count
+            0x00, 0x00, // peer index
+            0x00, 0x00, 0x00, 0x01, // originated time
+            0x00, 0x04, // attributes length
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+        ]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibUnicast::from_reader(
+            &mut cursor,
+            &peer_index_table(),
+            time,
+            Afi::Ipv6,
+            Safi::Unicast,
+            false,
+        );
+        assert!(rib.is_ok());
+        let rib = rib.unwrap();
+        assert_eq!(rib.sequence_number, 42);
+        assert_eq!(rib.prefix_len, 32);
+        assert_eq!(rib.prefix, "2001:db8::".parse::<IpAddr>().unwrap());
+        assert_eq!(rib.rib_entries.len(), 1);
+        assert_eq!(
+            rib.to_string(),
+            "TIME: 1970-01-01 00:00:00\nTYPE: TABLE_DUMP_V2/IPV6_UNICAST\nPREFIX: 2001:db8::/32\n\
+             SEQUENCE: 42\nFROM: 2001:db8::1 AS 65000\nORIGINATED: 1970-01-01 00:00:01\nORIGIN: IGP\n\n"
+        );
+    }
+
+    #[test]
+    fn test_rib_ipv6_multicast_from_reader() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x00, // sequence number
+            0x00, // prefix length /0
+            0x00, 0x00, // entry count
+        ]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibUnicast::from_reader(
+            &mut cursor,
+            &peer_index_table(),
+            time,
+            Afi::Ipv6,
+            Safi::Multicast,
+            false,
+        )
+        .unwrap();
+        assert_eq!(rib.safi, Safi::Multicast);
+        let json = serde_json::to_value(&rib).unwrap();
+        assert_eq!(json["safi"], "MULTICAST");
+    }
+
+    #[test]
+    fn test_rib_ipv6_unicast_bad_prefix_length() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x00, // sequence number
+            0x81, // prefix length /129
+        ]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibUnicast::from_reader(
+            &mut cursor,
+            &peer_index_table(),
+            time,
+            Afi::Ipv6,
+            Safi::Unicast,
+            false,
+        );
+        assert!(matches!(rib.unwrap_err(), Error::BadPrefixLength(129)));
+    }
+
+    #[test]
+    fn test_rib_ipv4_unicast_bad_prefix_length() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, 0x00, 0x00, // sequence number
+            0x21, // prefix length /33
+        ]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        let rib = RibUnicast::from_reader(
+            &mut cursor,
+            &peer_index_table(),
+            time,
+            Afi::Ipv4,
+            Safi::Unicast,
+            false,
+        );
+        assert!(matches!(rib.unwrap_err(), Error::BadPrefixLength(33)));
+    }
+}
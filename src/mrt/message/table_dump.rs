@@ -0,0 +1,243 @@
+use super::{Afi, BgpAttributes, MalformedRoutes, Prefix, RibEntry, Safi, WriteRecords};
+use crate::mrt::{Error, MRTHeader, MRTSubType};
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use serde::Serialize;
+use std::fmt::{self, Display};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Legacy TABLE_DUMP (v1) record, a single RIB entry per record
+#[derive(Debug, Serialize)]
+pub struct TableDump {
+    time: DateTime<chrono::Utc>,
+    view_number: u16,     // View number, normally 0
+    sequence_number: u16, // Sequence number of the RIB entry, wraps around
+    prefix: Prefix,       // network prefix
+    status: u8,           // Unused, should be 1
+    #[serde(flatten)]
+    rib_entry: RibEntry, // Peer and attributes of the route
+}
+
+impl TableDump {
+    pub fn from_reader<R: Read>(reader: &mut R, mrt_header: &MRTHeader) -> Result<Self, Error> {
+        let afi = match mrt_header.mrt_subtype {
+            MRTSubType::TableDumpAfiIpv6 => Afi::Ipv6,
+            _ => Afi::Ipv4,
+        };
+        let view_number = reader.read_u16::<BigEndian>()?;
+        let sequence_number = reader.read_u16::<BigEndian>()?;
+        // The prefix is stored on the full address length followed by its length
+        let addr = read_ip(reader, afi)?;
+        let prefix_len = reader.read_u8()?;
+        let max_prefix_len = match afi {
+            Afi::Ipv4 => 32,
+            Afi::Ipv6 => 128,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(Error::BadPrefixLength(prefix_len));
+        }
+        let status = reader.read_u8()?;
+        let originated_time = reader.read_u32::<BigEndian>()?;
+        let originated_time =
+            DateTime::from_timestamp(originated_time.into(), 0).ok_or(Error::BadRibEntryHeader)?;
+        let peer_ip = read_ip(reader, afi)?;
+        let peer_asn = reader.read_u16::<BigEndian>()? as u32;
+        let attribute_length = reader.read_u16::<BigEndian>()?;
+        let attributes = BgpAttributes::from_reader(reader, attribute_length, false)?;
+        Ok(TableDump {
+            time: mrt_header.ts,
+            view_number,
+            sequence_number,
+            prefix: Prefix {
+                addr,
+                len: prefix_len,
+            },
+            status,
+            rib_entry: RibEntry {
+                peer_index: None,
+                path_id: None,
+                peer_asn,
+                peer_ip,
+                peer_location: None,
+                originated_time,
+                attribute_length,
+                attributes,
+            },
+        })
+    }
+
+    pub fn time(&self) -> DateTime<chrono::Utc> {
+        self.time
+    }
+
+    pub fn view_number(&self) -> u16 {
+        self.view_number
+    }
+
+    pub fn sequence_number(&self) -> u16 {
+        self.sequence_number
+    }
+
+    pub fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    /// Peer and attributes of the route
+    pub fn rib_entry(&self) -> &RibEntry {
+        &self.rib_entry
+    }
+
+    pub fn into_rib_entry(self) -> RibEntry {
+        self.rib_entry
+    }
+}
+
+impl WriteRecords for TableDump {
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut csv_writer = csv::WriterBuilder::new()
+            .delimiter(b'|')
+            .has_headers(false)
+            .from_writer(writer);
+        csv_writer.serialize(self.rib_entry.to_csv_record(
+            "TABLE_DUMP",
+            self.time,
+            self.prefix.to_string(),
+            // TABLE_DUMP only holds unicast routes
+            Safi::Unicast.to_string(),
+        ))?;
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        writeln!(writer, "{}", json)?;
+        Ok(())
+    }
+}
+
+fn read_ip<R: Read>(reader: &mut R, afi: Afi) -> Result<IpAddr, Error> {
+    Ok(match afi {
+        Afi::Ipv4 => IpAddr::V4(Ipv4Addr::from_bits(reader.read_u32::<BigEndian>()?)),
+        Afi::Ipv6 => IpAddr::V6(Ipv6Addr::from_bits(reader.read_u128::<BigEndian>()?)),
+    })
+}
+
+impl MalformedRoutes for TableDump {
+    fn mark_malformed(&mut self) {
+        self.rib_entry.attributes.mark_error_action();
+    }
+
+    fn drop_malformed(&mut self) -> bool {
+        self.rib_entry.attributes.drop_malformed()
+    }
+}
+
+impl Display for TableDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TIME: {}\nTYPE: TABLE_DUMP/{}\nVIEW: {}\nSEQUENCE: {}\nPREFIX: {}\n",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            if self.prefix.addr.is_ipv6() {
+                "INET6"
+            } else {
+                "INET"
+            },
+            self.view_number,
+            self.sequence_number,
+            self.prefix,
+        )?;
+        if f.alternate() {
+            writeln!(f, "{:#}", self.rib_entry)
+        } else {
+            writeln!(f, "{}", self.rib_entry)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::MRTType;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reading_table_dump_ipv4() {
+        let mut cursor = Cursor::new(vec![
+            0x00, 0x00, // view number
+            0x00, 0x05, // sequence number
+            10, 0, 0, 0,    // prefix
+            0x08, // prefix length
+            0x01, // status
+            0x00, 0x00, 0x00, 0x01, // originated time
+            192, 0, 2, 1, // peer IP
+            0xfd, 0xe8, // peer AS 65000
+            0x00, 0x04, // attribute length
+            0x40, 0x01, 0x01, 0x02, // origin INCOMPLETE
+        ]);
+        let mrt_header = MRTHeader {
+            ts: DateTime::from_timestamp(0, 0).unwrap(),
+            mrt_type: MRTType::TableDump,
+            mrt_subtype: MRTSubType::TableDumpAfiIpv4,
+            length: 0,
+        };
+        let table_dump = TableDump::from_reader(&mut cursor, &mrt_header).unwrap();
+        assert_eq!(table_dump.sequence_number, 5);
+        assert_eq!(table_dump.prefix.to_string(), "10.0.0.0/8");
+        assert_eq!(table_dump.rib_entry.peer_asn, 65000);
+        assert_eq!(
+            table_dump.to_string(),
+            "TIME: 1970-01-01 00:00:00\nTYPE: TABLE_DUMP/INET\nVIEW: 0\nSEQUENCE: 5\n\
+             PREFIX: 10.0.0.0/8\nFROM: 192.0.2.1 AS 65000\nORIGINATED: 1970-01-01 00:00:01\n\
+             ORIGIN: INCOMPLETE\n\n"
+        );
+
+        let mut csv = Vec::new();
+        table_dump.write_csv_records(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "TABLE_DUMP|1970-01-01T00:00:00Z|B|192.0.2.1|65000|10.0.0.0/8||INCOMPLETE|UNICAST|||||NAG||||\n"
+        );
+    }
+
+    #[test]
+    fn test_reading_table_dump_ipv6() {
+        let mut data = vec![
+            0x00, 0x00, // view number
+            0x00, 0x01, // sequence number
+        ];
+        data.extend_from_slice(&"2001:db8::".parse::<Ipv6Addr>().unwrap().octets()); // prefix
+        data.extend_from_slice(&[
+            0x20, // prefix length
+            0x01, // status
+            0x00, 0x00, 0x00, 0x01, // originated time
+        ]);
+        data.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets()); // peer IP
+        data.extend_from_slice(&[
+            0xfd, 0xe8, // peer AS 65000
+            0x00, 0x00, // attribute length
+        ]);
+        let mrt_header = MRTHeader {
+            ts: DateTime::from_timestamp(0, 0).unwrap(),
+            mrt_type: MRTType::TableDump,
+            mrt_subtype: MRTSubType::TableDumpAfiIpv6,
+            length: 0,
+        };
+        let table_dump = TableDump::from_reader(&mut Cursor::new(data), &mrt_header).unwrap();
+        assert_eq!(table_dump.prefix.to_string(), "2001:db8::/32");
+        assert_eq!(
+            table_dump.rib_entry.peer_ip,
+            "2001:db8::1".parse::<IpAddr>().unwrap()
+        );
+        let json = serde_json::to_value(&table_dump).unwrap();
+        assert_eq!(json["prefix"], "2001:db8::/32");
+        assert_eq!(json["peer_asn"], 65000);
+        assert!(json.get("peer_index").is_none());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::mrt::Error;
+use std::fmt::Display;
+use std::io::Write;
+
+/// Output formats of the decoded records
+pub trait WriteRecords: Display {
+    /// Write the bgpdump-like `-m` lines, see [`super::CsvRecord`]
+    fn write_csv_records<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+
+    fn write_json_records<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+
+    /// Write the bgpdump-like multi-line records, `verbose` adds the flags
+    /// of the path attributes
+    fn write_multiline_records<W: Write>(
+        &self,
+        writer: &mut W,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        if verbose {
+            writeln!(writer, "{:#}", self)?;
+        } else {
+            writeln!(writer, "{}", self)?;
+        }
+        Ok(())
+    }
+}
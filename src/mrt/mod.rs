@@ -1,10 +1,25 @@
 pub mod error;
 pub mod message;
+pub mod mrt_element;
 pub mod mrt_header;
 pub mod mrt_message;
+pub mod mrt_message_reader;
+pub mod mrt_reader;
+pub mod mrt_slice_reader;
+#[cfg(test)]
+mod test_utils;
 pub use error::Error;
 pub use error::Result;
+pub use mrt_element::ElementType;
+pub use mrt_element::MRTElement;
+pub use mrt_element::MRTElementReader;
 pub use mrt_header::MRTHeader;
 pub use mrt_header::MRTSubType;
 pub use mrt_header::MRTType;
 pub use mrt_message::MRTMessage;
+pub use mrt_message_reader::MRTMessageReader;
+pub use mrt_reader::MRTReader;
+pub use mrt_reader::MRTRecord;
+pub use mrt_slice_reader::MRTRecordRef;
+pub use mrt_slice_reader::MRTSliceReader;
+pub use mrt_slice_reader::map_file;
@@ -0,0 +1,398 @@
+use super::message::{BgpAttributes, BgpState, GeoLocation, Nlri, Prefix, RibEntry, Safi};
+use super::{MRTReader, MRTRecord, Result};
+use chrono::DateTime;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::net::IpAddr;
+use std::sync::Arc;
+use strum_macros::Display;
+
+#[derive(Debug, Display, PartialEq, Eq, Clone, Copy)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ElementType {
+    Rib,      // Route of a TABLE_DUMP or TABLE_DUMP_V2 RIB
+    Announce, // Route announced by a BGP4MP UPDATE
+    Withdraw, // Route withdrawn by a BGP4MP UPDATE
+    State,    // BGP4MP state change of the peer
+}
+
+/// One route of a peer, or one state change, whatever the record it comes
+/// from. The routes of a record share its attributes. Labeled and VPN routes
+/// keep their labels and route distinguisher in `nlri`, the NLRI of an
+/// AFI/SAFI we don't decode give no route.
+#[derive(Debug, Clone)]
+pub struct MRTElement {
+    pub time: DateTime<chrono::Utc>,
+    pub element_type: ElementType,
+    pub peer_ip: IpAddr,
+    pub peer_asn: u32,
+    pub peer_location: Option<GeoLocation>, // RIB only, see MRTElementReader::locate_peers
+    pub safi: Option<Safi>,                 // None for state changes
+    pub nlri: Option<Nlri>,                 // None for state changes
+    pub path_id: Option<u32>,               // ADD-PATH path identifier, RIB only
+    pub attributes: Option<Arc<BgpAttributes>>, // RIB and announcements only
+    pub old_state: Option<BgpState>,        // State changes only
+    pub new_state: Option<BgpState>,        // State changes only
+}
+
+impl MRTElement {
+    fn route(
+        time: DateTime<chrono::Utc>,
+        element_type: ElementType,
+        peer_ip: IpAddr,
+        peer_asn: u32,
+        safi: Safi,
+        nlri: Nlri,
+        attributes: Option<Arc<BgpAttributes>>,
+    ) -> Self {
+        MRTElement {
+            time,
+            element_type,
+            peer_ip,
+            peer_asn,
+            peer_location: None,
+            safi: Some(safi),
+            nlri: Some(nlri),
+            path_id: None,
+            attributes,
+            old_state: None,
+            new_state: None,
+        }
+    }
+
+    fn rib(time: DateTime<chrono::Utc>, safi: Safi, nlri: Nlri, entry: RibEntry) -> Self {
+        MRTElement {
+            path_id: entry.path_id,
+            peer_location: entry.peer_location,
+            ..MRTElement::route(
+                time,
+                ElementType::Rib,
+                entry.peer_ip,
+                entry.peer_asn,
+                safi,
+                nlri,
+                Some(Arc::new(entry.attributes)),
+            )
+        }
+    }
+
+    /// IP prefix of the route, None for state changes
+    pub fn prefix(&self) -> Option<Prefix> {
+        self.nlri.as_ref().and_then(Nlri::prefix)
+    }
+}
+
+impl MRTRecord {
+    /// Flatten the record into its elements: one per RIB entry, one per
+    /// announced or withdrawn NLRI, one per state change. The peer tables,
+    /// the other BGP messages and the NLRI of an AFI/SAFI we don't decode
+    /// give none.
+    pub fn into_elements(self) -> Vec<MRTElement> {
+        let rib_elements = |time, safi, nlri: Nlri, entries: Vec<RibEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| MRTElement::rib(time, safi, nlri.clone(), entry))
+                .collect()
+        };
+        match self {
+            MRTRecord::RibUnicast(rib) => {
+                let nlri = Nlri::Prefix {
+                    prefix: rib.prefix(),
+                };
+                rib_elements(rib.time(), rib.safi(), nlri, rib.into_rib_entries())
+            }
+            MRTRecord::RibGeneric(rib) => match Safi::from_repr(rib.safi()) {
+                Some(safi) if !matches!(rib.nlri(), Nlri::Raw { .. }) => {
+                    let nlri = rib.nlri().clone();
+                    rib_elements(rib.time(), safi, nlri, rib.into_rib_entries())
+                }
+                _ => Vec::new(),
+            },
+            MRTRecord::TableDump(table_dump) => {
+                let time = table_dump.time();
+                let nlri = Nlri::Prefix {
+                    prefix: table_dump.prefix(),
+                };
+                // TABLE_DUMP only holds unicast routes
+                let entry = table_dump.into_rib_entry();
+                vec![MRTElement::rib(time, Safi::Unicast, nlri, entry)]
+            }
+            MRTRecord::Bgp4MpMessage(message) => {
+                let time = message.time();
+                let (peer_ip, peer_asn) = (message.header().peer_ip, message.header().peer_asn);
+                let Some(update) = message.into_update() else {
+                    return Vec::new();
+                };
+                // The unicast routes are taken out of the MP attributes when
+                // the UPDATE is read, those left are of another SAFI
+                let mp_routes = |safi: Option<u8>, nlri: &[Nlri]| {
+                    let safi = safi.and_then(Safi::from_repr);
+                    nlri.iter()
+                        .filter_map(|nlri| match (safi, nlri) {
+                            (_, Nlri::Raw { .. }) | (None, _) => None,
+                            (Some(safi), nlri) => Some((safi, nlri.clone())),
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let unicast = |prefixes: &[Prefix]| {
+                    prefixes
+                        .iter()
+                        .map(|prefix| (Safi::Unicast, Nlri::Prefix { prefix: *prefix }))
+                        .collect::<Vec<_>>()
+                };
+                let mut withdrawn = unicast(&update.withdrawn_routes);
+                if let Some(mp_unreach) = &update.attributes.bgp_mp_unreach_nlri {
+                    withdrawn.extend(mp_routes(
+                        Some(mp_unreach.safi),
+                        &mp_unreach.withdrawn_routes,
+                    ));
+                }
+                let mut announced = unicast(&update.announced_routes);
+                if let Some(mp_reach) = &update.attributes.bgp_mp_reach_nlri {
+                    announced.extend(mp_routes(mp_reach.safi, &mp_reach.nlri));
+                }
+                let attributes = Arc::new(update.attributes);
+                let route = |element_type, safi, nlri, attributes| {
+                    MRTElement::route(
+                        time,
+                        element_type,
+                        peer_ip,
+                        peer_asn,
+                        safi,
+                        nlri,
+                        attributes,
+                    )
+                };
+                withdrawn
+                    .into_iter()
+                    .map(|(safi, nlri)| route(ElementType::Withdraw, safi, nlri, None))
+                    .chain(announced.into_iter().map(|(safi, nlri)| {
+                        route(ElementType::Announce, safi, nlri, Some(attributes.clone()))
+                    }))
+                    .collect()
+            }
+            MRTRecord::Bgp4MpStateChange(state_change) => vec![MRTElement {
+                time: state_change.time(),
+                element_type: ElementType::State,
+                peer_ip: state_change.header().peer_ip,
+                peer_asn: state_change.header().peer_asn,
+                peer_location: None,
+                safi: None,
+                nlri: None,
+                path_id: None,
+                attributes: None,
+                old_state: Some(state_change.old_state()),
+                new_state: Some(state_change.new_state()),
+            }],
+            MRTRecord::PeerIndexTable(_)
+            | MRTRecord::GeoPeerTable(_)
+            | MRTRecord::Unknown { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Iterator over the elements of an MRT stream, see [`MRTRecord::into_elements`].
+/// The errors are those of [`MRTReader`].
+#[derive(Debug)]
+pub struct MRTElementReader<R> {
+    reader: MRTReader<R>,
+    pending: VecDeque<MRTElement>, // Elements of the last record not yet returned
+}
+
+impl<R: Read> MRTElementReader<R> {
+    pub fn new(reader: R) -> Self {
+        MRTElementReader {
+            reader: MRTReader::new(reader),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Resume on the next valid record after a corrupted one
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.reader = self.reader.recover(recover);
+        self
+    }
+
+    /// Locate the peers of the RIB elements with the GEO_PEER_TABLE following
+    /// their PEER_INDEX_TABLE, off by default
+    pub fn locate_peers(mut self, locate_peers: bool) -> Self {
+        self.reader = self.reader.locate_peers(locate_peers);
+        self
+    }
+}
+
+impl<R: Read> Iterator for MRTElementReader<R> {
+    type Item = Result<MRTElement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = self.pending.pop_front() {
+                return Some(Ok(element));
+            }
+            match self.reader.next()? {
+                Ok(record) => self.pending.extend(record.into_elements()),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::test_utils::{PEER_INDEX_TABLE, record, rib_ipv4_unicast};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_iterating_elements() {
+        let bgp4mp_header = [
+            0xfd, 0xe8, // peer AS 65000
+            0xfd, 0xe9, // local AS 65001
+            0x00, 0x00, // interface index
+            0x00, 0x01, // AFI IPv4
+            192, 0, 2, 1, // peer IP
+            192, 0, 2, 2, // local IP
+        ];
+        let mut data = record(0x0d, 1, &PEER_INDEX_TABLE);
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(8)));
+        data.extend(record(
+            0x0d,
+            3,
+            &[
+                0, 0, 0, 1, // sequence number
+                4, 0xe0, // prefix 224.0.0.0/4
+                0, 1, // entry count
+                0, 0, // peer index
+                0x65, 0x53, 0xf1, 0x00, // originated time
+                0, 4, // attribute length
+                0x40, 0x01, 0x01, 0x00, // origin IGP
+            ],
+        ));
+        // labeled routes keep their labels
+        data.extend(record(
+            0x0d,
+            6,
+            &[
+                0, 0, 0, 2, // sequence number
+                0, 1, 4, // IPv4 MPLS labeled
+                32, 0x00, 0x01, 0x01, 10, // label 16, 10.0.0.0/8
+                0, 1, // entry count
+                0, 0, // peer index
+                0x65, 0x53, 0xf1, 0x00, // originated time
+                0, 4, // attribute length
+                0x40, 0x01, 0x01, 0x00, // origin IGP
+            ],
+        ));
+        let mut update = bgp4mp_header.to_vec();
+        update.extend_from_slice(&[0xff; 16]); // marker
+        update.extend_from_slice(&[
+            0x00, 0x2b, // length 43
+            0x02, // type UPDATE
+            0x00, 0x02, // withdrawn routes length
+            0x08, 0x0a, // 10.0.0.0/8
+            0x00, 0x0b, // path attribute length 11
+            0x40, 0x01, 0x01, 0x00, // origin IGP
+            0x40, 0x03, 0x04, 192, 0, 2, 1, // next hop
+            0x18, 0xc6, 0x33, 0x64, // 198.51.100.0/24
+            0x10, 0xac, 0x10, // 172.16.0.0/16
+        ]);
+        data.extend(record(0x10, 1, &update));
+        let mut state_change = bgp4mp_header.to_vec();
+        state_change.extend_from_slice(&[
+            0x00, 0x06, // old state Established
+            0x00, 0x01, // new state Idle
+        ]);
+        data.extend(record(0x10, 0, &state_change));
+
+        let elements = MRTElementReader::new(Cursor::new(data))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let summary = elements
+            .iter()
+            .map(|element| {
+                let prefix = element.prefix().map(|prefix| prefix.to_string());
+                (
+                    element.element_type,
+                    element.safi,
+                    prefix.unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let unicast = Some(Safi::Unicast);
+        assert_eq!(
+            summary,
+            [
+                (ElementType::Rib, unicast, "10.0.0.0/8".to_string()),
+                (
+                    ElementType::Rib,
+                    Some(Safi::Multicast),
+                    "224.0.0.0/4".to_string()
+                ),
+                (
+                    ElementType::Rib,
+                    Some(Safi::MplsLabel),
+                    "10.0.0.0/8".to_string()
+                ),
+                (ElementType::Withdraw, unicast, "10.0.0.0/8".to_string()),
+                (
+                    ElementType::Announce,
+                    unicast,
+                    "198.51.100.0/24".to_string()
+                ),
+                (ElementType::Announce, unicast, "172.16.0.0/16".to_string()),
+                (ElementType::State, None, String::new()),
+            ]
+        );
+        assert!(elements.iter().all(|element| element.peer_asn == 65000));
+        assert!(matches!(
+            &elements[2].nlri,
+            Some(Nlri::Labeled { labels, .. }) if labels == &[16]
+        ));
+        assert!(elements[3].attributes.is_none());
+        // the announcements of an UPDATE share its attributes
+        assert!(Arc::ptr_eq(
+            elements[4].attributes.as_ref().unwrap(),
+            elements[5].attributes.as_ref().unwrap()
+        ));
+        assert_eq!(
+            elements[0].attributes.as_ref().unwrap().origin_string(),
+            "IGP"
+        );
+        assert_eq!(elements[6].new_state, Some(BgpState::Idle));
+        assert_eq!(elements[6].element_type.to_string(), "STATE");
+    }
+
+    #[test]
+    fn test_locating_peers() {
+        let mut geo_peer_table = vec![
+            0, 0, 0, 1, // collector_bgp_id
+            0, 0, // view_name_len
+        ];
+        geo_peer_table.extend_from_slice(&48.85f32.to_be_bytes()); // collector latitude
+        geo_peer_table.extend_from_slice(&2.35f32.to_be_bytes()); // collector longitude
+        geo_peer_table.extend_from_slice(&[
+            0, 0x01, // nentries
+            0x00, // peer_type (IPv4)
+            0, 0, 0, 2, // bgp_id
+            192, 0, 2, 1, // peer_ip
+        ]);
+        geo_peer_table.extend_from_slice(&(-33.87f32).to_be_bytes()); // peer latitude
+        geo_peer_table.extend_from_slice(&151.21f32.to_be_bytes()); // peer longitude
+        let mut data = record(0x0d, 1, &PEER_INDEX_TABLE);
+        data.extend(record(0x0d, 7, &geo_peer_table));
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(8)));
+
+        let locations = |locate_peers| {
+            MRTElementReader::new(Cursor::new(data.clone()))
+                .locate_peers(locate_peers)
+                .map(|element| element.unwrap().peer_location)
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(locations(false)[..], [None]));
+        let [Some(location)] = locations(true)[..] else {
+            panic!("the peer is not located");
+        };
+        assert_eq!(location.latitude, -33.87);
+        assert_eq!(location.longitude, 151.21);
+    }
+}
@@ -4,15 +4,96 @@ use chrono::{Utc, prelude::DateTime};
 use std::io::Read;
 use strum_macros::FromRepr;
 
-#[derive(Debug, PartialEq, FromRepr, Clone, Copy)]
-#[repr(u16)]
+/// MRT subtypes, their numbering depends on the MRT type so they are decoded
+/// with [`MRTSubType::from_repr`]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MRTSubType {
-    PeerIndexTable = 1,   // Peer index type
-    RibIpV4Unicast = 2,   // RIB IPv4 Unicast subtype
-    RibIpV4Multicast = 3, // RIB IPv4 Multicast subtype
-    RibIpV6Unicast = 4,   // RIB IPv6 Unicast subtype
-    RibIpV6Multicast = 5, // RIB IPv6 Multicast subtype
-    RibGeneric = 6,       // RIB Generic subtype
+    // TABLE_DUMP subtypes
+    TableDumpAfiIpv4, // IPv4 RIB entry
+    TableDumpAfiIpv6, // IPv6 RIB entry
+    // TABLE_DUMP_V2 subtypes
+    PeerIndexTable,          // Peer index type
+    RibIpV4Unicast,          // RIB IPv4 Unicast subtype
+    RibIpV4Multicast,        // RIB IPv4 Multicast subtype
+    RibIpV6Unicast,          // RIB IPv6 Unicast subtype
+    RibIpV6Multicast,        // RIB IPv6 Multicast subtype
+    RibGeneric,              // RIB Generic subtype
+    GeoPeerTable,            // Collector and peer locations (RFC 6397)
+    RibIpV4UnicastAddPath,   // RIB IPv4 Unicast ADD-PATH subtype (RFC 8050)
+    RibIpV4MulticastAddPath, // RIB IPv4 Multicast ADD-PATH subtype (RFC 8050)
+    RibIpV6UnicastAddPath,   // RIB IPv6 Unicast ADD-PATH subtype (RFC 8050)
+    RibIpV6MulticastAddPath, // RIB IPv6 Multicast ADD-PATH subtype (RFC 8050)
+    RibGenericAddPath,       // RIB Generic ADD-PATH subtype (RFC 8050)
+    // BGP4MP and BGP4MP_ET subtypes
+    Bgp4MpStateChange,     // Peer state change, 2-byte ASN
+    Bgp4MpMessage,         // BGP message, 2-byte ASN
+    Bgp4MpMessageAs4,      // BGP message, 4-byte ASN
+    Bgp4MpStateChangeAs4,  // Peer state change, 4-byte ASN
+    Bgp4MpMessageLocal,    // Locally generated BGP message, 2-byte ASN
+    Bgp4MpMessageAs4Local, // Locally generated BGP message, 4-byte ASN
+}
+
+impl MRTSubType {
+    /// Decode the subtype number of a record of type `mrt_type`
+    pub fn from_repr(mrt_type: MRTType, subtype: u16) -> Option<Self> {
+        match (mrt_type, subtype) {
+            (MRTType::TableDump, 1) => Some(MRTSubType::TableDumpAfiIpv4),
+            (MRTType::TableDump, 2) => Some(MRTSubType::TableDumpAfiIpv6),
+            (MRTType::TableDumpV2, 1) => Some(MRTSubType::PeerIndexTable),
+            (MRTType::TableDumpV2, 2) => Some(MRTSubType::RibIpV4Unicast),
+            (MRTType::TableDumpV2, 3) => Some(MRTSubType::RibIpV4Multicast),
+            (MRTType::TableDumpV2, 4) => Some(MRTSubType::RibIpV6Unicast),
+            (MRTType::TableDumpV2, 5) => Some(MRTSubType::RibIpV6Multicast),
+            (MRTType::TableDumpV2, 6) => Some(MRTSubType::RibGeneric),
+            (MRTType::TableDumpV2, 7) => Some(MRTSubType::GeoPeerTable),
+            (MRTType::TableDumpV2, 8) => Some(MRTSubType::RibIpV4UnicastAddPath),
+            (MRTType::TableDumpV2, 9) => Some(MRTSubType::RibIpV4MulticastAddPath),
+            (MRTType::TableDumpV2, 10) => Some(MRTSubType::RibIpV6UnicastAddPath),
+            (MRTType::TableDumpV2, 11) => Some(MRTSubType::RibIpV6MulticastAddPath),
+            (MRTType::TableDumpV2, 12) => Some(MRTSubType::RibGenericAddPath),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 0) => Some(MRTSubType::Bgp4MpStateChange),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 1) => Some(MRTSubType::Bgp4MpMessage),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 4) => Some(MRTSubType::Bgp4MpMessageAs4),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 5) => Some(MRTSubType::Bgp4MpStateChangeAs4),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 6) => Some(MRTSubType::Bgp4MpMessageLocal),
+            (MRTType::Bgp4Mp | MRTType::Bgp4MpEt, 7) => Some(MRTSubType::Bgp4MpMessageAs4Local),
+            _ => None,
+        }
+    }
+
+    /// Whether the RIB of this subtype is a multicast one
+    pub fn is_multicast(&self) -> bool {
+        matches!(
+            self,
+            MRTSubType::RibIpV4Multicast
+                | MRTSubType::RibIpV6Multicast
+                | MRTSubType::RibIpV4MulticastAddPath
+                | MRTSubType::RibIpV6MulticastAddPath
+        )
+    }
+
+    /// Whether the RIB of this subtype is an IPv6 one
+    pub fn is_ipv6(&self) -> bool {
+        matches!(
+            self,
+            MRTSubType::RibIpV6Unicast
+                | MRTSubType::RibIpV6Multicast
+                | MRTSubType::RibIpV6UnicastAddPath
+                | MRTSubType::RibIpV6MulticastAddPath
+        )
+    }
+
+    /// Whether the RIB entries of this subtype carry an ADD-PATH path identifier
+    pub fn is_add_path(&self) -> bool {
+        matches!(
+            self,
+            MRTSubType::RibIpV4UnicastAddPath
+                | MRTSubType::RibIpV4MulticastAddPath
+                | MRTSubType::RibIpV6UnicastAddPath
+                | MRTSubType::RibIpV6MulticastAddPath
+                | MRTSubType::RibGenericAddPath
+        )
+    }
 }
 
 impl std::fmt::Display for MRTSubType {
@@ -35,6 +116,16 @@ pub enum MRTType {
     OspfV3Et = 49,
 }
 
+impl MRTType {
+    /// Whether the record carries the extra microsecond timestamp of the `*_ET` types
+    pub fn is_extended_timestamp(&self) -> bool {
+        matches!(
+            self,
+            MRTType::Bgp4MpEt | MRTType::IsisEt | MRTType::OspfV3Et
+        )
+    }
+}
+
 impl std::fmt::Display for MRTType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -42,12 +133,11 @@ impl std::fmt::Display for MRTType {
 }
 
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub struct MRTHeader {
-    pub ts: DateTime<Utc>,       // "Timestamp" in seconds since epoch"
-    pub mrt_type: MRTType,       // Type of the MRT header message
+    pub ts: DateTime<Utc>, // "Timestamp" since epoch, with microseconds for *_ET types
+    pub mrt_type: MRTType, // Type of the MRT header message
     pub mrt_subtype: MRTSubType, // Subtype of the MRT header message
-    pub length: u32,             // Length of the MRT header message
+    pub length: u32,       // Length of the payload, *_ET microsecond field excluded
 }
 
 impl MRTHeader {
@@ -57,9 +147,21 @@ impl MRTHeader {
         let mrt_subtype = reader.read_u16::<BigEndian>()?;
         let length = reader.read_u32::<BigEndian>()?;
         let mrt_type = MRTType::from_repr(mrt_type).ok_or(Error::BadMrtType(mrt_type))?;
-        let mrt_subtype =
-            MRTSubType::from_repr(mrt_subtype).ok_or(Error::BadMrtSubtype(mrt_subtype))?;
-        let ts = DateTime::from_timestamp(ts as i64, 0).ok_or(Error::BadMrtHeader)?;
+        let mrt_subtype = MRTSubType::from_repr(mrt_type, mrt_subtype)
+            .ok_or(Error::BadMrtSubtype(mrt_subtype))?;
+        // The *_ET types start the payload with a microsecond timestamp,
+        // counted in the length of the message
+        let (microseconds, length) = if mrt_type.is_extended_timestamp() {
+            let length = length.checked_sub(4).ok_or(Error::BadMrtHeader)?;
+            (reader.read_u32::<BigEndian>()?, length)
+        } else {
+            (0, length)
+        };
+        if microseconds >= 1_000_000 {
+            return Err(Error::BadMrtHeader);
+        }
+        let ts =
+            DateTime::from_timestamp(ts as i64, microseconds * 1000).ok_or(Error::BadMrtHeader)?;
         Ok(MRTHeader {
             ts,
             mrt_type,
@@ -108,11 +210,68 @@ mod tests {
         let mut cursor = Cursor::new(vec![
             0, 0, 0, 0, // ts
             0, 0x0d, // mrt_type
-            0, 0x08, // mrt_subtype
+            0, 0x0d, // mrt_subtype
             0, 0, 0, 0, // length
         ]);
         let header = MRTHeader::from_reader(&mut cursor);
         assert!(header.is_err());
-        assert!(matches!(header.unwrap_err(), Error::BadMrtSubtype(0x08)));
+        assert!(matches!(header.unwrap_err(), Error::BadMrtSubtype(0x0d)));
+    }
+
+    #[test]
+    fn test_reading_mrt_header_add_path_subtype() {
+        let mut cursor = Cursor::new(vec![
+            0, 0, 0, 0, // ts
+            0, 0x0d, // mrt_type
+            0, 0x0a, // mrt_subtype
+            0, 0, 0, 0, // length
+        ]);
+        let header = MRTHeader::from_reader(&mut cursor).unwrap();
+        assert_eq!(header.mrt_subtype, MRTSubType::RibIpV6UnicastAddPath);
+        assert!(header.mrt_subtype.is_add_path());
+        assert!(!MRTSubType::RibIpV6Unicast.is_add_path());
+    }
+
+    #[test]
+    fn test_reading_mrt_header_bgp4mp_subtype() {
+        let mut cursor = Cursor::new(vec![
+            0, 0, 0, 0, // ts
+            0, 0x10, // mrt_type
+            0, 0x04, // mrt_subtype
+            0, 0, 0, 0, // length
+        ]);
+        let header = MRTHeader::from_reader(&mut cursor).unwrap();
+        assert_eq!(header.mrt_type, MRTType::Bgp4Mp);
+        assert_eq!(header.mrt_subtype, MRTSubType::Bgp4MpMessageAs4);
+    }
+
+    #[test]
+    fn test_reading_mrt_header_extended_timestamp() {
+        let mut cursor = Cursor::new(vec![
+            0, 0, 0, 0x01, // ts
+            0, 0x11, // mrt_type
+            0, 0x04, // mrt_subtype
+            0, 0, 0, 0x06, // length
+            0, 0x01, 0xe2, 0x40, // microseconds 123456
+            0xff, 0xff, // payload
+        ]);
+        let header = MRTHeader::from_reader(&mut cursor).unwrap();
+        assert_eq!(header.mrt_type, MRTType::Bgp4MpEt);
+        assert_eq!(header.ts, DateTime::from_timestamp(1, 123_456_000).unwrap());
+        assert_eq!(header.length, 2);
+        assert_eq!(cursor.position(), 16);
+    }
+
+    #[test]
+    fn test_reading_mrt_header_extended_timestamp_bad_length() {
+        let mut cursor = Cursor::new(vec![
+            0, 0, 0, 0x01, // ts
+            0, 0x11, // mrt_type
+            0, 0x04, // mrt_subtype
+            0, 0, 0, 0x02, // length
+            0, 0x01, 0xe2, 0x40, // microseconds
+        ]);
+        let header = MRTHeader::from_reader(&mut cursor);
+        assert!(matches!(header.unwrap_err(), Error::BadMrtHeader));
     }
 }
@@ -1,6 +1,9 @@
 use super::{Error, MRTHeader};
 use std::io::{Cursor, Read};
 
+/// Size of the common MRT header, before the *_ET microsecond field
+pub const MRT_HEADER_LENGTH: usize = 12;
+
 #[derive(Debug)]
 pub struct MRTMessage {
     pub header: MRTHeader,
@@ -8,20 +11,55 @@ pub struct MRTMessage {
 }
 
 impl MRTMessage {
+    /// Read the next record, the payload of a record with an unknown type or
+    /// subtype is skipped so that the caller can carry on with the next one
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let header = MRTHeader::from_reader(reader)?;
-        let mut payload = vec![0u8; header.length as usize];
-        reader.read_exact(&mut payload)?;
+        let mut common = [0u8; MRT_HEADER_LENGTH];
+        reader.read_exact(&mut common)?;
+        let (_, _, _, length) = common_fields(&common);
+        // The buffer only grows with the data actually read, a corrupted
+        // length doesn't allocate gigabytes
+        let mut body = Vec::new();
+        reader.take(length.into()).read_to_end(&mut body)?;
+        if body.len() < length as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        MRTMessage::from_parts(&common, body)
+    }
+
+    /// Build a record from its common header and the `length` bytes that
+    /// follow it, *_ET microsecond field included
+    pub fn from_parts(common: &[u8; MRT_HEADER_LENGTH], mut body: Vec<u8>) -> Result<Self, Error> {
+        let header = match MRTHeader::from_reader(&mut (&common[..]).chain(&body[..])) {
+            Err(Error::BadMrtType(_) | Error::BadMrtSubtype(_)) => {
+                let (_, mrt_type, mrt_subtype, _) = common_fields(common);
+                return Err(Error::UnsupportedMrtRecord(mrt_type, mrt_subtype));
+            }
+            header => header?,
+        };
+        // Drop the microsecond field already decoded in the header
+        body.drain(..body.len() - header.length as usize);
         Ok(MRTMessage {
             header,
-            payload: Cursor::new(payload),
+            payload: Cursor::new(body),
         })
     }
 }
 
+/// Raw timestamp, type, subtype and length of a common MRT header
+pub fn common_fields(common: &[u8; MRT_HEADER_LENGTH]) -> (u32, u16, u16, u32) {
+    (
+        u32::from_be_bytes([common[0], common[1], common[2], common[3]]),
+        u16::from_be_bytes([common[4], common[5]]),
+        u16::from_be_bytes([common[6], common[7]]),
+        u32::from_be_bytes([common[8], common[9], common[10], common[11]]),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mrt::MRTSubType;
     use std::io::Cursor;
 
     #[test]
@@ -39,4 +77,26 @@ mod tests {
         message.unwrap().payload.read_exact(&mut buf).unwrap();
         assert_eq!(buf, [0x1, 0x1, 0x1, 0x1]);
     }
+
+    #[test]
+    fn test_skipping_unsupported_record() {
+        let mut cursor = Cursor::new(vec![
+            0, 0, 0, 0, // ts
+            0, 0x0d, // mrt_type
+            0, 0x63, // unknown mrt_subtype
+            0, 0, 0, 0x02, // length
+            0xff, 0xff, // payload
+            0, 0, 0, 0, // ts
+            0, 0x0d, // mrt_type
+            0, 0x01, // mrt_subtype
+            0, 0, 0, 0, // length
+        ]);
+        let message = MRTMessage::from_reader(&mut cursor);
+        assert!(matches!(
+            message.unwrap_err(),
+            Error::UnsupportedMrtRecord(0x0d, 0x63)
+        ));
+        let message = MRTMessage::from_reader(&mut cursor).unwrap();
+        assert_eq!(message.header.mrt_subtype, MRTSubType::PeerIndexTable);
+    }
 }
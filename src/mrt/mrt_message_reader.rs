@@ -0,0 +1,262 @@
+use super::mrt_message::{MRT_HEADER_LENGTH, common_fields};
+use super::{Error, MRTMessage, MRTSubType, MRTType, Result};
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+/// Records longer than this are taken for corrupted headers
+pub const MAX_RECORD_LENGTH: u32 = 16 * 1024 * 1024;
+
+/// Headers older than 1990-01-01 are not taken for records when resynchronising
+const MIN_TIMESTAMP: i64 = 631_152_000;
+
+/// Read the MRT records of a stream, keeping track of the byte offset and the
+/// number of the records to report truncated or corrupted ones, and to
+/// resynchronise on the next record after a corrupted one
+#[derive(Debug)]
+pub struct MRTMessageReader<R> {
+    reader: R,
+    pending: VecDeque<u8>, // Bytes read ahead, given back before the stream
+    offset: u64,           // Offset of the next byte in the stream
+    record: u64,           // Number of records read so far
+    recover: bool,         // Headers with a bad timestamp are corrupted records
+}
+
+impl<R: Read> MRTMessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        MRTMessageReader {
+            reader,
+            pending: VecDeque::new(),
+            offset: 0,
+            record: 0,
+            recover: false,
+        }
+    }
+
+    /// Also report the headers with an insane timestamp as corrupted records,
+    /// rather than reading the length they claim, so that the caller can
+    /// resynchronise on them. The records of an unknown type are still
+    /// skipped with their length.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Offset of the next record in the stream
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Number of records read so far, the failed ones included
+    pub fn record(&self) -> u64 {
+        self.record
+    }
+
+    /// Read the next record, None at the end of the stream. A record cut by
+    /// the end of the stream is a `TruncatedRecord`, a header with an
+    /// impossible length a `CorruptedRecord`, both with the offset and the
+    /// number of the record
+    pub fn read_message(&mut self) -> Result<Option<MRTMessage>> {
+        let offset = self.offset;
+        let mut common = [0u8; MRT_HEADER_LENGTH];
+        // A compressed stream cut short fails with UnexpectedEof
+        let truncated = |e: io::Error, record| match e.kind() {
+            io::ErrorKind::UnexpectedEof => Error::TruncatedRecord(offset, record),
+            _ => e.into(),
+        };
+        let read = self
+            .read_full(&mut common)
+            .map_err(|e| truncated(e, self.record + 1))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.record += 1;
+        if read < MRT_HEADER_LENGTH {
+            return Err(Error::TruncatedRecord(offset, self.record));
+        }
+        let (_, _, _, length) = common_fields(&common);
+        let corrupted = if self.recover {
+            !has_sane_fields(&common)
+        } else {
+            !has_valid_length(&common)
+        };
+        if corrupted {
+            // Keep the header for resync() to scan from its second byte
+            self.unread(&common);
+            return Err(Error::CorruptedRecord(offset, self.record));
+        }
+        let mut body = Vec::new();
+        let from_pending = self.pending.len().min(length as usize);
+        body.extend(self.pending.drain(..from_pending));
+        (&mut self.reader)
+            .take(u64::from(length) - from_pending as u64)
+            .read_to_end(&mut body)
+            .map_err(|e| truncated(e, self.record))?;
+        self.offset += body.len() as u64;
+        if body.len() < length as usize {
+            return Err(Error::TruncatedRecord(offset, self.record));
+        }
+        match MRTMessage::from_parts(&common, body) {
+            // Only a bad microsecond field gets there, the record is skipped
+            Err(Error::BadMrtHeader) => Err(Error::CorruptedRecord(offset, self.record)),
+            message => message.map(Some),
+        }
+    }
+
+    /// Skip forward to the next plausible record header: sane timestamp,
+    /// known type and subtype, bounded length. Returns the number of bytes
+    /// skipped, None when the end of the stream comes first
+    pub fn resync(&mut self) -> Result<Option<u64>> {
+        let mut skipped = 0;
+        loop {
+            if !self.fill(1)? {
+                return Ok(None);
+            }
+            self.pending.pop_front();
+            self.offset += 1;
+            skipped += 1;
+            if !self.fill(MRT_HEADER_LENGTH)? {
+                self.offset += self.pending.len() as u64;
+                self.pending.clear();
+                return Ok(None);
+            }
+            let mut common = [0u8; MRT_HEADER_LENGTH];
+            for (byte, pending) in common.iter_mut().zip(&self.pending) {
+                *byte = *pending;
+            }
+            if is_plausible_header(&common) {
+                return Ok(Some(skipped));
+            }
+        }
+    }
+
+    /// Read as much of `buf` as the stream holds, pending bytes first
+    fn read_full(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            let count = match self.pending.pop_front() {
+                Some(byte) => {
+                    buf[read] = byte;
+                    1
+                }
+                None => match self.reader.read(&mut buf[read..]) {
+                    Ok(0) => break,
+                    Ok(count) => count,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                },
+            };
+            read += count;
+        }
+        self.offset += read as u64;
+        Ok(read)
+    }
+
+    /// Give bytes back, they are read again before the rest of the stream
+    fn unread(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter().rev() {
+            self.pending.push_front(*byte);
+        }
+        self.offset -= bytes.len() as u64;
+    }
+
+    /// Read ahead until `count` bytes are pending, false at the end of the stream
+    fn fill(&mut self, count: usize) -> io::Result<bool> {
+        let mut buf = [0u8; 4096];
+        while self.pending.len() < count {
+            match self.reader.read(&mut buf) {
+                Ok(0) => return Ok(false),
+                Ok(read) => self.pending.extend(&buf[..read]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Whether the length of a common header is possible: bounded, and room for
+/// the microsecond field of the *_ET types
+pub(crate) fn has_valid_length(common: &[u8; MRT_HEADER_LENGTH]) -> bool {
+    let (_, mrt_type, _, length) = common_fields(common);
+    let extended_timestamp =
+        MRTType::from_repr(mrt_type).is_some_and(|mrt_type| mrt_type.is_extended_timestamp());
+    length <= MAX_RECORD_LENGTH && (length >= 4 || !extended_timestamp)
+}
+
+/// Whether the fields of a common header that don't depend on its type are
+/// sane: timestamp between 1990 and tomorrow, possible length
+fn has_sane_fields(common: &[u8; MRT_HEADER_LENGTH]) -> bool {
+    let (ts, _, _, _) = common_fields(common);
+    let max_timestamp = Utc::now().timestamp() + 86_400;
+    (MIN_TIMESTAMP..=max_timestamp).contains(&i64::from(ts)) && has_valid_length(common)
+}
+
+/// Whether a common header looks like the start of a record when scanning
+/// for one: known type and subtype, sane timestamp and length
+fn is_plausible_header(common: &[u8; MRT_HEADER_LENGTH]) -> bool {
+    let (_, mrt_type, mrt_subtype, _) = common_fields(common);
+    MRTType::from_repr(mrt_type)
+        .is_some_and(|mrt_type| MRTSubType::from_repr(mrt_type, mrt_subtype).is_some())
+        && has_sane_fields(common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::test_utils::record;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_resync_after_corrupted_record() {
+        let mut data = record(0x0d, 1, &[1, 2, 3, 4]);
+        // a header with an impossible length, then some garbage
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0x0d, 0, 0x02, 0xff, 0xff, 0xff, 0xff, 0xaa]);
+        data.extend(record(0x0d, 2, &[5, 6]));
+        let mut reader = MRTMessageReader::new(Cursor::new(data));
+        assert!(reader.read_message().unwrap().is_some());
+        assert_eq!(reader.offset(), 16);
+        let error = reader.read_message().unwrap_err();
+        assert!(matches!(error, Error::CorruptedRecord(16, 2)));
+        assert_eq!(reader.resync().unwrap(), Some(13));
+        assert_eq!(reader.offset(), 29);
+        let message = reader.read_message().unwrap().unwrap();
+        assert_eq!(message.header.mrt_subtype, MRTSubType::RibIpV4Unicast);
+        assert_eq!(message.payload.get_ref(), &vec![5, 6]);
+        assert!(reader.read_message().unwrap().is_none());
+        assert_eq!(reader.record(), 3);
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let mut data = record(0x0d, 1, &[1, 2, 3, 4]);
+        data.extend_from_slice(&record(0x0d, 2, &[5, 6, 7, 8])[..14]);
+        let mut reader = MRTMessageReader::new(Cursor::new(data));
+        assert!(reader.read_message().unwrap().is_some());
+        let error = reader.read_message().unwrap_err();
+        assert!(matches!(error, Error::TruncatedRecord(16, 2)));
+        assert_eq!(reader.resync().unwrap(), None);
+    }
+
+    #[test]
+    fn test_recover_from_corrupted_header() {
+        let mut data = record(0x0d, 1, &[1, 2, 3, 4]);
+        // a header with a garbage timestamp, type and length, then the next record
+        data.extend_from_slice(&[0, 0, 0x12, 0x34, 0x12, 0x34, 0, 0x01, 0, 0x10, 0, 0]);
+        data.extend(record(0x0d, 2, &[5, 6]));
+        let mut reader = MRTMessageReader::new(Cursor::new(data.clone()));
+        assert!(reader.read_message().unwrap().is_some());
+        // without recovery its garbage length eats the next record
+        assert!(matches!(
+            reader.read_message().unwrap_err(),
+            Error::TruncatedRecord(16, 2)
+        ));
+        let mut reader = MRTMessageReader::new(Cursor::new(data)).recover(true);
+        assert!(reader.read_message().unwrap().is_some());
+        let error = reader.read_message().unwrap_err();
+        assert!(matches!(error, Error::CorruptedRecord(16, 2)));
+        assert_eq!(reader.resync().unwrap(), Some(12));
+        let message = reader.read_message().unwrap().unwrap();
+        assert_eq!(message.payload.get_ref(), &vec![5, 6]);
+    }
+}
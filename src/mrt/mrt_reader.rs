@@ -0,0 +1,304 @@
+use super::message::{
+    Afi, Bgp4MpMessage, Bgp4MpStateChange, GeoPeerTable, PeerIndexTable, RibGeneric, RibUnicast,
+    Safi, TableDump,
+};
+use super::{Error, MRTHeader, MRTMessage, MRTMessageReader, MRTSubType, Result};
+use std::io::Read;
+
+/// A decoded MRT record
+#[derive(Debug)]
+pub enum MRTRecord {
+    PeerIndexTable(PeerIndexTable),
+    GeoPeerTable(GeoPeerTable),
+    /// RIB_IPV4_UNICAST, RIB_IPV4_MULTICAST, RIB_IPV6_UNICAST and
+    /// RIB_IPV6_MULTICAST, ADD-PATH or not
+    RibUnicast(RibUnicast),
+    RibGeneric(RibGeneric),
+    TableDump(TableDump),
+    Bgp4MpMessage(Bgp4MpMessage),
+    Bgp4MpStateChange(Bgp4MpStateChange),
+    /// Record of a type or subtype we don't decode, its payload is skipped
+    Unknown {
+        mrt_type: u16,
+        mrt_subtype: u16,
+    },
+}
+
+/// Iterator over the decoded records of an MRT stream. The RIB records are
+/// decoded with the last PEER_INDEX_TABLE, located with the GEO_PEER_TABLE
+/// that follows it if any.
+///
+/// A record that fails to decode yields an `InvalidRecord` error with its
+/// offset and the iteration goes on with the next one. The iteration stops
+/// after a truncated or corrupted record, unless [`MRTReader::recover`] is set
+/// to resume on the next valid record, and after an error of the underlying
+/// reader.
+#[derive(Debug)]
+pub struct MRTReader<R> {
+    reader: MRTMessageReader<R>,
+    peer_index_table: Option<PeerIndexTable>, // Table of the following RIB records
+    header: Option<MRTHeader>,                // Header of the last record read
+    recover: bool,                            // Resynchronise after a corrupted record
+    locate_peers: bool,                       // Apply the GEO_PEER_TABLE to the peers
+    done: bool,                               // End of the stream or fatal error
+}
+
+impl<R: Read> MRTReader<R> {
+    pub fn new(reader: R) -> Self {
+        MRTReader {
+            reader: MRTMessageReader::new(reader),
+            peer_index_table: None,
+            header: None,
+            recover: false,
+            locate_peers: false,
+            done: false,
+        }
+    }
+
+    /// Resume on the next valid record after a corrupted one
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.reader = self.reader.recover(recover);
+        self.recover = recover;
+        self
+    }
+
+    /// Locate the peers of the RIB entries with the GEO_PEER_TABLE following
+    /// their PEER_INDEX_TABLE, off by default
+    pub fn locate_peers(mut self, locate_peers: bool) -> Self {
+        self.locate_peers = locate_peers;
+        self
+    }
+
+    /// Header of the last record read, whether it decoded or not. None for
+    /// the records of an unknown type or subtype and after a read error.
+    pub fn header(&self) -> Option<&MRTHeader> {
+        self.header.as_ref()
+    }
+
+    /// Peer index table the RIB records are decoded with
+    pub fn peer_index_table(&self) -> Option<&PeerIndexTable> {
+        self.peer_index_table.as_ref()
+    }
+
+    /// Offset of the next record in the stream
+    pub fn offset(&self) -> u64 {
+        self.reader.offset()
+    }
+
+    fn decode(&mut self, message: MRTMessage) -> Result<MRTRecord> {
+        let MRTMessage {
+            header,
+            mut payload,
+        } = message;
+        let subtype = header.mrt_subtype;
+        // IPv4, IPv6 and multicast RIBs share one parser, only the AFI and
+        // the SAFI differ
+        let afi = if subtype.is_ipv6() {
+            Afi::Ipv6
+        } else {
+            Afi::Ipv4
+        };
+        let safi = if subtype.is_multicast() {
+            Safi::Multicast
+        } else {
+            Safi::Unicast
+        };
+        let peer_index_table = || {
+            self.peer_index_table
+                .as_ref()
+                .ok_or(Error::MissingPeerIndexTable)
+        };
+        let record = match subtype {
+            MRTSubType::PeerIndexTable => {
+                let peer_index_table = PeerIndexTable::from_reader(&mut payload)?;
+                self.peer_index_table = Some(peer_index_table.clone());
+                MRTRecord::PeerIndexTable(peer_index_table)
+            }
+            MRTSubType::GeoPeerTable => {
+                let geo_peer_table = GeoPeerTable::from_reader(&mut payload)?;
+                if let Some(peer_index_table) = &mut self.peer_index_table
+                    && self.locate_peers
+                {
+                    peer_index_table.set_locations(&geo_peer_table);
+                }
+                MRTRecord::GeoPeerTable(geo_peer_table)
+            }
+            MRTSubType::RibIpV4Unicast
+            | MRTSubType::RibIpV4Multicast
+            | MRTSubType::RibIpV4UnicastAddPath
+            | MRTSubType::RibIpV4MulticastAddPath
+            | MRTSubType::RibIpV6Unicast
+            | MRTSubType::RibIpV6Multicast
+            | MRTSubType::RibIpV6UnicastAddPath
+            | MRTSubType::RibIpV6MulticastAddPath => {
+                MRTRecord::RibUnicast(RibUnicast::from_reader(
+                    &mut payload,
+                    peer_index_table()?,
+                    header.ts,
+                    afi,
+                    safi,
+                    subtype.is_add_path(),
+                )?)
+            }
+            MRTSubType::RibGeneric | MRTSubType::RibGenericAddPath => {
+                MRTRecord::RibGeneric(RibGeneric::from_reader(
+                    &mut payload,
+                    peer_index_table()?,
+                    header.ts,
+                    subtype.is_add_path(),
+                )?)
+            }
+            MRTSubType::TableDumpAfiIpv4 | MRTSubType::TableDumpAfiIpv6 => {
+                MRTRecord::TableDump(TableDump::from_reader(&mut payload, &header)?)
+            }
+            MRTSubType::Bgp4MpMessage
+            | MRTSubType::Bgp4MpMessageAs4
+            | MRTSubType::Bgp4MpMessageLocal
+            | MRTSubType::Bgp4MpMessageAs4Local => {
+                MRTRecord::Bgp4MpMessage(Bgp4MpMessage::from_reader(&mut payload, &header)?)
+            }
+            MRTSubType::Bgp4MpStateChange | MRTSubType::Bgp4MpStateChangeAs4 => {
+                MRTRecord::Bgp4MpStateChange(Bgp4MpStateChange::from_reader(&mut payload, &header)?)
+            }
+        };
+        Ok(record)
+    }
+}
+
+impl<R: Read> Iterator for MRTReader<R> {
+    type Item = Result<MRTRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let offset = self.reader.offset();
+        self.header = None;
+        match self.reader.read_message() {
+            Ok(Some(message)) => {
+                self.header = Some(message.header);
+                Some(
+                    self.decode(message).map_err(|e| {
+                        Error::InvalidRecord(offset, self.reader.record(), Box::new(e))
+                    }),
+                )
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(Error::UnsupportedMrtRecord(mrt_type, mrt_subtype)) => {
+                Some(Ok(MRTRecord::Unknown {
+                    mrt_type,
+                    mrt_subtype,
+                }))
+            }
+            Err(e @ Error::CorruptedRecord(..)) if self.recover => {
+                match self.reader.resync() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => self.done = true,
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                Some(Err(e))
+            }
+            Err(e @ (Error::CorruptedRecord(..) | Error::TruncatedRecord(..))) => {
+                self.done = true;
+                Some(Err(e))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::test_utils::{PEER_INDEX_TABLE, record, rib_ipv4_unicast};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_iterating_records() {
+        // a RIB before the peer index table can't be decoded
+        let mut data = record(0x0d, 2, &rib_ipv4_unicast(8));
+        data.extend(record(0x0d, 1, &PEER_INDEX_TABLE));
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(8)));
+        data.extend(record(0x0d, 0x63, &[0xff]));
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(33)));
+        let records = MRTReader::new(Cursor::new(data)).collect::<Vec<_>>();
+        assert_eq!(records.len(), 5);
+        match &records[0] {
+            Err(Error::InvalidRecord(0, 1, e)) => {
+                assert!(matches!(**e, Error::MissingPeerIndexTable))
+            }
+            record => panic!("unexpected {:?}", record),
+        }
+        assert!(matches!(records[1], Ok(MRTRecord::PeerIndexTable(_))));
+        match &records[2] {
+            Ok(MRTRecord::RibUnicast(rib)) => {
+                assert_eq!(rib.prefix().to_string(), "10.0.0.0/8");
+                assert_eq!(rib.rib_entries()[0].peer_asn, 65000);
+            }
+            record => panic!("unexpected {:?}", record),
+        }
+        assert!(matches!(
+            records[3],
+            Ok(MRTRecord::Unknown {
+                mrt_type: 13,
+                mrt_subtype: 0x63
+            })
+        ));
+        match &records[4] {
+            Err(Error::InvalidRecord(offset, 5, e)) => {
+                assert_eq!(*offset, 32 + 31 + 32 + 13);
+                assert!(matches!(**e, Error::BadPrefixLength(33)));
+            }
+            record => panic!("unexpected {:?}", record),
+        }
+    }
+
+    #[test]
+    fn test_header_of_the_last_record() {
+        let mut data = record(0x0d, 1, &PEER_INDEX_TABLE);
+        data.extend(record(0x0d, 0x63, &[0xff]));
+        let mut reader = MRTReader::new(Cursor::new(data));
+        assert!(reader.header().is_none());
+        assert!(matches!(
+            reader.next(),
+            Some(Ok(MRTRecord::PeerIndexTable(_)))
+        ));
+        let header = reader.header().unwrap();
+        assert_eq!(header.mrt_subtype, MRTSubType::PeerIndexTable);
+        assert!(matches!(reader.next(), Some(Ok(MRTRecord::Unknown { .. }))));
+        assert!(reader.header().is_none());
+    }
+
+    #[test]
+    fn test_recover_skips_unknown_records() {
+        let mut data = record(0x0d, 1, &PEER_INDEX_TABLE);
+        // a valid record of an unknown type, its payload looks like a record
+        data.extend(record(99, 1, &record(0x0d, 2, &rib_ipv4_unicast(16))));
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(8)));
+        let records = MRTReader::new(Cursor::new(data))
+            .recover(true)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(matches!(
+            records[1],
+            MRTRecord::Unknown {
+                mrt_type: 99,
+                mrt_subtype: 1
+            }
+        ));
+        match &records[2] {
+            MRTRecord::RibUnicast(rib) => assert_eq!(rib.prefix().to_string(), "10.0.0.0/8"),
+            record => panic!("unexpected {:?}", record),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use super::message::{Afi, RibRef};
+use super::mrt_message::{MRT_HEADER_LENGTH, common_fields};
+use super::mrt_message_reader::has_valid_length;
+use super::{Error, MRTHeader, MRTSubType, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+/// An MRT record borrowed from the input, its payload is only decoded on
+/// access. The RIB_IPV4_* and RIB_IPV6_* records have a borrowed view,
+/// [`MRTRecordRef::rib`], whose entries and attributes are decoded lazily.
+/// The other records (PEER_INDEX_TABLE, RIB_GENERIC, TABLE_DUMP, BGP4MP...)
+/// are not covered: their payload, which implements `Read`, goes through the
+/// allocating parsers of `mrt::message`.
+#[derive(Debug, Clone, Copy)]
+pub enum MRTRecordRef<'a> {
+    Known {
+        header: MRTHeader,
+        payload: &'a [u8], // Payload, *_ET microsecond field excluded
+    },
+    /// Record of a type or subtype we don't decode, like
+    /// [`super::MRTRecord::Unknown`]
+    Unknown {
+        mrt_type: u16,
+        mrt_subtype: u16,
+        payload: &'a [u8],
+    },
+}
+
+/// Read the MRT records of a byte slice without copying them, e.g. of a file
+/// mapped in memory with [`map_file`]. Like [`super::MRTReader`] it reports
+/// truncated and corrupted records with their offset and stops there, the
+/// records of an unknown type or subtype are returned as
+/// [`MRTRecordRef::Unknown`].
+#[derive(Debug, Clone)]
+pub struct MRTSliceReader<'a> {
+    data: &'a [u8],
+    offset: usize, // Offset of the next record in the data
+    record: u64,   // Number of records read so far
+}
+
+impl<'a> MRTRecordRef<'a> {
+    /// Header of the record, None for the records of an unknown type
+    pub fn header(&self) -> Option<&MRTHeader> {
+        match self {
+            MRTRecordRef::Known { header, .. } => Some(header),
+            MRTRecordRef::Unknown { .. } => None,
+        }
+    }
+
+    pub fn payload(&self) -> &'a [u8] {
+        match self {
+            MRTRecordRef::Known { payload, .. } | MRTRecordRef::Unknown { payload, .. } => payload,
+        }
+    }
+
+    /// The RIB_IPV4_* and RIB_IPV6_* records of TABLE_DUMP_V2, None for the
+    /// other records
+    pub fn rib(&self) -> Result<Option<RibRef<'a>>> {
+        let MRTRecordRef::Known { header, payload } = self else {
+            return Ok(None);
+        };
+        let afi = match header.mrt_subtype {
+            MRTSubType::RibIpV4Unicast
+            | MRTSubType::RibIpV4Multicast
+            | MRTSubType::RibIpV4UnicastAddPath
+            | MRTSubType::RibIpV4MulticastAddPath => Afi::Ipv4,
+            MRTSubType::RibIpV6Unicast
+            | MRTSubType::RibIpV6Multicast
+            | MRTSubType::RibIpV6UnicastAddPath
+            | MRTSubType::RibIpV6MulticastAddPath => Afi::Ipv6,
+            _ => return Ok(None),
+        };
+        let add_path = header.mrt_subtype.is_add_path();
+        RibRef::from_bytes(payload, header.ts, afi, add_path).map(Some)
+    }
+}
+
+impl<'a> MRTSliceReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        MRTSliceReader {
+            data,
+            offset: 0,
+            record: 0,
+        }
+    }
+
+    /// Offset of the next record in the data
+    pub fn offset(&self) -> u64 {
+        self.offset as u64
+    }
+
+    /// Number of records read so far, the failed ones included
+    pub fn record(&self) -> u64 {
+        self.record
+    }
+
+    fn stop(&mut self) {
+        self.offset = self.data.len();
+    }
+}
+
+impl<'a> Iterator for MRTSliceReader<'a> {
+    type Item = Result<MRTRecordRef<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = &self.data[self.offset..];
+        if data.is_empty() {
+            return None;
+        }
+        let offset = self.offset as u64;
+        self.record += 1;
+        let Some(common) = data.first_chunk::<MRT_HEADER_LENGTH>() else {
+            self.stop();
+            return Some(Err(Error::TruncatedRecord(offset, self.record)));
+        };
+        let (_, mrt_type, mrt_subtype, length) = common_fields(common);
+        if !has_valid_length(common) {
+            self.stop();
+            return Some(Err(Error::CorruptedRecord(offset, self.record)));
+        }
+        let Some(record) = data.get(..MRT_HEADER_LENGTH + length as usize) else {
+            self.stop();
+            return Some(Err(Error::TruncatedRecord(offset, self.record)));
+        };
+        self.offset += record.len();
+        let header = match MRTHeader::from_reader(&mut { record }) {
+            Ok(header) => header,
+            Err(Error::BadMrtType(_) | Error::BadMrtSubtype(_)) => {
+                return Some(Ok(MRTRecordRef::Unknown {
+                    mrt_type,
+                    mrt_subtype,
+                    payload: &record[MRT_HEADER_LENGTH..],
+                }));
+            }
+            // Only a bad microsecond field gets there, the record is skipped
+            Err(_) => return Some(Err(Error::CorruptedRecord(offset, self.record))),
+        };
+        Some(Ok(MRTRecordRef::Known {
+            payload: &record[record.len() - header.length as usize..],
+            header,
+        }))
+    }
+}
+
+/// Map an uncompressed MRT file in memory, to be read with [`MRTSliceReader`]
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is mapped, see
+/// `memmap2::Mmap::map`
+pub unsafe fn map_file<P: AsRef<Path>>(path: P) -> Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: upheld by the caller
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mrt::message::PeerIndexTable;
+    use crate::mrt::test_utils::{PEER_INDEX_TABLE, record, rib_ipv4_unicast};
+
+    #[test]
+    fn test_reading_slice() {
+        let mut data = record(0x0d, 1, &PEER_INDEX_TABLE);
+        data.extend(record(0x0d, 0x63, &[0xff]));
+        data.extend(record(0x0d, 2, &rib_ipv4_unicast(8)));
+        data.extend_from_slice(&record(0x0d, 2, &[0, 0, 0, 1])[..14]);
+
+        let mut reader = MRTSliceReader::new(&data);
+        let record = reader.next().unwrap().unwrap();
+        assert_eq!(
+            record.header().unwrap().mrt_subtype,
+            MRTSubType::PeerIndexTable
+        );
+        assert!(record.rib().unwrap().is_none());
+        let peer_index_table = PeerIndexTable::from_reader(&mut record.payload()).unwrap();
+        // like MRTReader, records of an unknown subtype are returned
+        let record = reader.next().unwrap().unwrap();
+        assert!(matches!(
+            record,
+            MRTRecordRef::Unknown {
+                mrt_type: 13,
+                mrt_subtype: 0x63,
+                payload: &[0xff]
+            }
+        ));
+        assert!(record.rib().unwrap().is_none());
+        let record = reader.next().unwrap().unwrap();
+        // the payload borrows from the input
+        assert!(std::ptr::eq(record.payload().as_ptr(), &data[56]));
+        let rib = record.rib().unwrap().unwrap();
+        assert_eq!(rib.prefix().to_string(), "10.0.0.0/8");
+        let entry = rib.entries().next().unwrap().unwrap();
+        let entry = entry.decode(&peer_index_table).unwrap();
+        assert_eq!(entry.peer_asn, 65000);
+        assert_eq!(entry.attributes.origin_string(), "IGP");
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(Error::TruncatedRecord(76, 4))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_mapping_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let mmap = unsafe { map_file(path) }.unwrap();
+        assert_eq!(&mmap[..], std::fs::read(path).unwrap());
+    }
+}
@@ -0,0 +1,38 @@
+//! Records shared by the tests of the readers
+
+/// MRT record of the given type and subtype, 2023-11-14 timestamp
+pub(crate) fn record(mrt_type: u8, subtype: u8, payload: &[u8]) -> Vec<u8> {
+    let mut record = vec![
+        0x65, 0x53, 0xf1, 0x00, // ts 2023-11-14
+        0, mrt_type, // type
+        0, subtype, // subtype
+    ];
+    record.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    record.extend_from_slice(payload);
+    record
+}
+
+/// PEER_INDEX_TABLE payload with a single peer, 192.0.2.1 AS 65000
+pub(crate) const PEER_INDEX_TABLE: [u8; 19] = [
+    0, 0, 0, 1, // collector_bgp_id
+    0, 0, // view_name_len
+    0, 0x01, // nentries
+    0x00, // peer_type (IPv4, ASN 16 bits)
+    0, 0, 0, 2, // bgp_id
+    192, 0, 2, 1, // peer_ip
+    0xfd, 0xe8, // peer_asn 65000
+];
+
+/// RIB_IPV4_UNICAST payload of 10.0.0.0/`prefix_len` with one entry of the
+/// peer of [`PEER_INDEX_TABLE`]
+pub(crate) fn rib_ipv4_unicast(prefix_len: u8) -> Vec<u8> {
+    vec![
+        0, 0, 0, 0, // sequence number
+        prefix_len, 10, // prefix
+        0, 1, // entry count
+        0, 0, // peer index
+        0x65, 0x53, 0xf1, 0x00, // originated time
+        0, 4, // attribute length
+        0x40, 0x01, 0x01, 0x00, // origin IGP
+    ]
+}